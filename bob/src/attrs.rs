@@ -0,0 +1,759 @@
+//! Parsing of the attributes used to configure builders.
+//!
+//! Problems are reported to `Diagnostics` and sensible defaults are returned instead,
+//! so that the rest of the builder can still be generated.
+
+use std::cell::RefCell;
+use std::cmp::{max, min};
+use std::fmt::Display;
+
+use proc_macro2::{Delimiter, Group, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::{parse_quote, Attribute, Error, Expr, ExprLit, Ident, Lit, LitStr, Meta, Path, Token, WherePredicate};
+
+/// Collects errors found from the input so that all of them can be reported at once.
+///
+/// Errors are reported with `compile_error!` and rest of the builder is still generated,
+/// so that they don't cause unrelated errors.
+#[derive(Default)]
+pub struct Diagnostics {
+    /// Messages with spans of the first and the last token they point at.
+    errors: RefCell<Vec<(Span, Span, String)>>,
+}
+
+impl Diagnostics {
+    /// Reports error pointing at given tokens.
+    pub fn error<T: ToTokens, D: Display>(&self, tokens: T, message: D) {
+        let mut tokens = tokens.into_token_stream().into_iter();
+        let start = tokens.next().map_or_else(Span::call_site, |t| t.span());
+        let end = tokens.last().map_or(start, |t| t.span());
+        self.errors.borrow_mut().push((start, end, message.to_string()));
+    }
+
+    /// Reports error returned by syn.
+    pub fn push(&self, error: Error) {
+        for e in error {
+            self.errors.borrow_mut().push((e.span(), e.span(), e.to_string()));
+        }
+    }
+
+    /// Creates `compile_error!` invocation for every reported error.
+    ///
+    /// Invocation is spanned from the first token to the last one so that the whole erroneous part is highlighted.
+    pub fn to_tokens(&self) -> TokenStream {
+        self.errors.borrow()
+            .iter()
+            .flat_map(|&(start, end, ref message)| {
+                let mut bang = Punct::new('!', Spacing::Alone);
+                bang.set_span(start);
+                let mut args = Group::new(Delimiter::Brace, quote!(#message));
+                args.set_span(end);
+                vec![
+                    TokenTree::Ident(Ident::new("compile_error", start)),
+                    TokenTree::Punct(bang),
+                    TokenTree::Group(args),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Attributes supported on structs and enums.
+pub const ITEM_ATTRS: &[&str] = &["builder_names", "builder_prefix", "builder_validate", "builder_docs", "builder_derive", "builder_into", "builder_mode", "builder_setter_style", "builder_overwrite", "builder_required", "builder_option_aliases", "builder_error", "builder_to_builder"];
+/// Attributes supported on enum variants.
+pub const VARIANT_ATTRS: &[&str] = &["builder_names", "builder_prefix", "builder_docs"];
+/// Attributes supported on fields.
+pub const FIELD_ATTRS: &[&str] = &["builder_prefix", "builder_docs", "builder_default", "builder_name", "builder_into", "builder_each", "builder_overwrite", "builder_option_names", "builder_required", "builder_optional", "builder_validate_field", "builder_debug"];
+
+/// Converts path into string the way it's written.
+fn path_name(path: &Path) -> String {
+    path.segments.iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Checks that only attributes supported in this part of the input are used.
+pub fn check_attrs(diagnostics: &Diagnostics, attrs: &[Attribute], supported: &[&str]) {
+    for attr in attrs {
+        let name = path_name(attr.path());
+        if name.starts_with("builder_") && !supported.contains(&&name[..]) {
+            diagnostics.error(attr.path(), format_args!("`#[{}]` isn't supported here", name));
+        }
+    }
+}
+
+/// Gets attribute with given name and reports error if there is more than one of them.
+fn find_attr<'a>(diagnostics: &Diagnostics, attrs: &'a [Attribute], name: &str) -> Option<&'a Meta> {
+    let mut iter = attrs.iter()
+        .filter(|a| a.path().is_ident(name));
+    let result = iter.next();
+    for duplicate in iter {
+        diagnostics.error(duplicate, format_args!("only one `#[{}]` attribute is supported", name));
+    }
+    result.map(|a| &a.meta)
+}
+
+/// Gets items of `#[name(...)]` attribute.
+fn attr_list(diagnostics: &Diagnostics, meta: &Meta) -> Vec<Meta> {
+    if let Meta::List(ref list) = *meta {
+        match list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+            Ok(items) => items.into_iter().collect(),
+            Err(e) => {
+                diagnostics.push(e);
+                vec![]
+            },
+        }
+    } else {
+        diagnostics.error(meta, format_args!("expected `#[{}(...)]`", path_name(meta.path())));
+        vec![]
+    }
+}
+
+/// Gets value of `#[name = "..."]` attribute.
+fn attr_str(diagnostics: &Diagnostics, meta: &Meta) -> Option<LitStr> {
+    if let Meta::NameValue(ref nv) = *meta {
+        lit_str(diagnostics, &path_name(&nv.path), &nv.value)
+    } else {
+        diagnostics.error(meta, format_args!("expected `#[{} = \"...\"]`", path_name(meta.path())));
+        None
+    }
+}
+
+/// Gets string literal given for key.
+fn lit_str(diagnostics: &Diagnostics, key: &str, value: &Expr) -> Option<LitStr> {
+    if let Expr::Lit(ExprLit { lit: Lit::Str(ref lit), .. }) = *value {
+        Some(lit.clone())
+    } else {
+        diagnostics.error(value, format_args!("expected string literal for `{}`", key));
+        None
+    }
+}
+
+/// Parses identifier given for key.
+fn parse_ident_value(diagnostics: &Diagnostics, key: &str, lit: &LitStr) -> Option<Ident> {
+    match lit.parse() {
+        Ok(ident) => Some(ident),
+        Err(_) => {
+            diagnostics.error(lit, format_args!("`{}` given for `{}` isn't valid identifier", lit.value(), key));
+            None
+        },
+    }
+}
+
+/// Parses path given for key.
+fn parse_path_value(diagnostics: &Diagnostics, key: &str, lit: &LitStr) -> Option<Path> {
+    match lit.parse() {
+        Ok(path) => Some(path),
+        Err(_) => {
+            diagnostics.error(lit, format_args!("`{}` given for `{}` isn't valid path", lit.value(), key));
+            None
+        },
+    }
+}
+
+/// Reports key that isn't supported by attribute.
+fn unknown_key(diagnostics: &Diagnostics, attr: &str, key: &Path, supported: &[&str]) {
+    let name = path_name(key);
+    diagnostics.error(key, format_args!("unknown key `{}` in `#[{}]`{}", name, attr, did_you_mean(&name, supported, "keys")));
+}
+
+/// Creates hint for misspelled name that suggests similar supported name and lists all of them.
+fn did_you_mean(name: &str, supported: &[&str], what: &str) -> String {
+    let suggestion = supported.iter()
+        .map(|s| (edit_distance(name, s), s))
+        // Allow about one typo per three characters.
+        .filter(|&(d, s)| d <= max(1, s.len() / 3))
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, s)| format!(", did you mean `{}`?", s))
+        .unwrap_or_else(|| ".".to_owned());
+    let supported = supported.iter()
+        .map(|s| format!("`{}`", s))
+        .collect::<Vec<_>>();
+    let supported = match supported.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => "none".to_owned(),
+    };
+    format!("{} Supported {} are {}.", suggestion, what, supported)
+}
+
+/// Calculates edit distance between two strings where swapping adjacent characters counts as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // Distances between prefixes of the strings.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..b.len() + 1).collect();
+    for i in 1..a.len() + 1 {
+        for j in 1..b.len() + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = min(d[i - 1][j - 1] + cost, min(d[i - 1][j], d[i][j - 1]) + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = min(d[i][j], d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Reports item in attribute list that isn't of the expected form.
+fn unexpected_item(diagnostics: &Diagnostics, attr: &str, item: &Meta, expected: &str) {
+    diagnostics.error(item, format_args!("unexpected `{}` in `#[{}]`, expected {}", quote!(#item), attr, expected));
+}
+
+/// Gets names given as `key = "name"` pairs in attribute and falls back to default ones for keys not present.
+fn get_names<const N: usize>(diagnostics: &Diagnostics, attrs: &[Attribute], attr: &str, keys: [&str; N], default: [Ident; N]) -> [Ident; N] {
+    let mut names = default;
+    let items = find_attr(diagnostics, attrs, attr)
+        .map(|meta| attr_list(diagnostics, meta))
+        .unwrap_or_default();
+    for item in &items {
+        if let Meta::NameValue(ref nv) = *item {
+            let key = path_name(&nv.path);
+            let name = match keys.iter().position(|k| *k == key) {
+                Some(i) => &mut names[i],
+                None => {
+                    unknown_key(diagnostics, attr, &nv.path, &keys);
+                    continue;
+                },
+            };
+            if let Some(ident) = lit_str(diagnostics, &key, &nv.value).and_then(|v| parse_ident_value(diagnostics, &key, &v)) {
+                *name = ident;
+            }
+        } else {
+            unexpected_item(diagnostics, attr, item, "`key = \"name\"`");
+        }
+    }
+    names
+}
+
+/// Gets name of the generated error type given with `#[builder_error]` or `#[builder_error = "Name"]`.
+///
+/// Falls back to default name if it isn't given, and to no error type if there is no attribute.
+pub fn get_error_name(diagnostics: &Diagnostics, attrs: &[Attribute], default: Ident) -> Option<Ident> {
+    let meta = find_attr(diagnostics, attrs, "builder_error")?;
+    if let Meta::Path(_) = *meta {
+        return Some(default);
+    }
+    let name = attr_str(diagnostics, meta).and_then(|v| parse_ident_value(diagnostics, "builder_error", &v));
+    Some(name.unwrap_or(default))
+}
+
+/// Gets builders, builders constructors and build methods names based on attribute and falls back to default ones if no attribute present.
+pub fn get_builder_names(diagnostics: &Diagnostics, attrs: &[Attribute], default: (Ident, Ident, Ident)) -> (Ident, Ident, Ident) {
+    let [builder, new, build] = get_names(diagnostics, attrs, "builder_names", ["builder", "new", "build"], [default.0, default.1, default.2]);
+    (builder, new, build)
+}
+
+/// Gets names of the setters that take `Option` and clear the value of optional `Option` field.
+///
+/// Falls back to default ones if no attribute present.
+pub fn get_option_names(diagnostics: &Diagnostics, attrs: &[Attribute], default: (Ident, Ident)) -> (Ident, Ident) {
+    let [opt, clear] = get_names(diagnostics, attrs, "builder_option_names", ["opt", "clear"], [default.0, default.1]);
+    (opt, clear)
+}
+
+/// Documentation given with `#[builder_docs]` attribute.
+#[derive(Default)]
+pub struct Docs {
+    pub builder: Option<String>,
+    pub new: Option<String>,
+    pub build: Option<String>,
+    pub setter: Option<String>,
+    /// If set, given documentation is appended to the generated one instead of replacing it.
+    pub append: bool,
+}
+
+impl Docs {
+    /// Combines given documentation with the generated one.
+    pub fn apply(&self, custom: &Option<String>, generated: String) -> String {
+        match *custom {
+            Some(ref custom) if self.append => format!("{}\n\n{}", generated, custom),
+            Some(ref custom) => custom.clone(),
+            None => generated,
+        }
+    }
+}
+
+/// Gets documentation based on attribute and falls back to generated documentation if no attribute present.
+///
+/// Keys that can be given depend on what is documented.
+pub fn get_docs(diagnostics: &Diagnostics, attrs: &[Attribute], keys: &[&str]) -> Docs {
+    let mut docs = Docs::default();
+    let supported = [keys, &["append"]].concat();
+    let items = find_attr(diagnostics, attrs, "builder_docs")
+        .map(|meta| attr_list(diagnostics, meta))
+        .unwrap_or_default();
+    for item in &items {
+        match *item {
+            Meta::NameValue(ref nv) => {
+                let key = path_name(&nv.path);
+                let doc = match &key[..] {
+                    "builder" => &mut docs.builder,
+                    "new" => &mut docs.new,
+                    "build" => &mut docs.build,
+                    "setter" => &mut docs.setter,
+                    _ => {
+                        unknown_key(diagnostics, "builder_docs", &nv.path, &supported);
+                        continue;
+                    },
+                };
+                if !keys.contains(&&key[..]) {
+                    diagnostics.error(&nv.path, format_args!("`{}` in `#[builder_docs]` isn't supported here{}", key, did_you_mean(&key, &supported, "keys")));
+                    continue;
+                }
+                *doc = lit_str(diagnostics, &key, &nv.value).map(|v| v.value());
+            },
+            Meta::Path(ref path) if path.is_ident("append") => docs.append = true,
+            _ => unexpected_item(diagnostics, "builder_docs", item, "`key = \"documentation\"` or `append`"),
+        }
+    }
+    docs
+}
+
+/// How field is shown in the debug output of builder.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DebugStyle {
+    /// Value of the field is shown.
+    Show,
+    /// Only the state of the field is shown, but not its value.
+    Redact,
+    /// Field is left out.
+    Skip,
+}
+
+/// Gets how field is shown in debug output based on `#[builder_debug(...)]` and falls back to showing it if no attribute present.
+pub fn get_debug_style(diagnostics: &Diagnostics, attrs: &[Attribute]) -> DebugStyle {
+    let items = find_attr(diagnostics, attrs, "builder_debug")
+        .map(|meta| attr_list(diagnostics, meta))
+        .unwrap_or_default();
+    let mut style = None;
+    for item in &items {
+        let new = match *item {
+            Meta::Path(ref path) if path.is_ident("skip") => DebugStyle::Skip,
+            Meta::Path(ref path) if path.is_ident("redact") => DebugStyle::Redact,
+            Meta::Path(ref path) => {
+                unknown_key(diagnostics, "builder_debug", path, &["skip", "redact"]);
+                continue;
+            },
+            _ => {
+                unexpected_item(diagnostics, "builder_debug", item, "`skip` or `redact`");
+                continue;
+            },
+        };
+        if style.is_some() {
+            diagnostics.error(item, "only one of `skip` and `redact` can be given");
+        } else {
+            style = Some(new);
+        }
+    }
+    style.unwrap_or(DebugStyle::Show)
+}
+
+/// Gets doc comments of an item.
+pub fn get_doc_comments(attrs: &[Attribute]) -> Vec<String> {
+    attrs.iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| {
+            if let Meta::NameValue(ref nv) = a.meta {
+                if let Expr::Lit(ExprLit { lit: Lit::Str(ref value), .. }) = nv.value {
+                    return Some(value.value());
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Traits whose impls are generated for builder instead of deriving them,
+/// so that they don't require anything from the types used as the states of fields.
+const IMPLEMENTED: &[&str] = &["Clone", "Debug", "PartialEq", "Eq", "Hash", "Default"];
+/// Traits of serde that have generated impls for builder if `serde` feature is enabled.
+pub const SERDE_TRAITS: &[&str] = &["Serialize", "Deserialize"];
+
+/// Traits that aren't derived for the types used as the states of fields.
+///
+/// Traits that construct values would allow creating builder whose required fields are in set state without values.
+pub const NOT_FOR_STATES: &[&str] = &["Default", "Serialize", "Deserialize"];
+
+/// Traits that have generated impls for builder.
+fn implemented_traits() -> Vec<&'static str> {
+    let serde = if cfg!(feature = "serde") { SERDE_TRAITS } else { &[] };
+    [IMPLEMENTED, serde].concat()
+}
+
+/// Gets name of the trait that has generated impl, if path refers to one.
+///
+/// Traits can be given by their names or by their paths in `std`, `core` or `serde`.
+fn trait_name(path: &Path, traits: &[&'static str]) -> Option<&'static str> {
+    let segments = path.segments.iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>();
+    let name = traits.iter().find(|&&t| segments.last().is_some_and(|l| l == t))?;
+    let module = match *name {
+        "Clone" => "clone",
+        "Debug" => "fmt",
+        "PartialEq" | "Eq" => "cmp",
+        "Hash" => "hash",
+        "Default" => "default",
+        _ => "",
+    };
+    let segments = segments.iter().map(|s| &s[..]).collect::<Vec<_>>();
+    let resolved = match segments[..] {
+        [_] => path.leading_colon.is_none(),
+        ["std", m, _] | ["core", m, _] => m == module,
+        ["serde", _] => SERDE_TRAITS.contains(name),
+        _ => false,
+    };
+    if resolved {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Trait that has generated impl for builder.
+#[derive(Clone)]
+pub struct Implemented {
+    /// Name of the trait, which is the same regardless of how its path was given.
+    pub name: &'static str,
+    pub path: Path,
+    /// Bounds given with `Trait(bound = "...")` that replace the inferred ones.
+    pub bounds: Option<Vec<WherePredicate>>,
+}
+
+/// Traits given for builder with `#[builder_derive(...)]`.
+#[derive(Clone, Default)]
+pub struct Derives {
+    /// Traits that have generated impls.
+    pub implemented: Vec<Implemented>,
+    /// Traits that are derived for builder as they are.
+    pub derived: Vec<Path>,
+}
+
+impl Derives {
+    /// Gets trait with given name if its impl is generated.
+    pub fn implemented(&self, name: &str) -> Option<&Implemented> {
+        self.implemented.iter().find(|i| i.name == name)
+    }
+}
+
+/// Parses where predicates given for key.
+fn parse_predicates_value(diagnostics: &Diagnostics, key: &str, lit: &LitStr) -> Option<Vec<WherePredicate>> {
+    match lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated) {
+        Ok(predicates) => Some(predicates.into_iter().collect()),
+        Err(_) => {
+            diagnostics.error(lit, format_args!("`{}` given for `{}` isn't valid where clause", lit.value(), key));
+            None
+        },
+    }
+}
+
+/// Gets traits that are implemented for builder.
+///
+/// Custom bounds can be given only for the traits that have generated impls.
+pub fn get_derives(diagnostics: &Diagnostics, attrs: &[Attribute]) -> Derives {
+    let mut derives = Derives::default();
+    let implemented = implemented_traits();
+    let items = attrs.iter()
+        .filter(|a| a.path().is_ident("builder_derive"))
+        .flat_map(|a| attr_list(diagnostics, &a.meta));
+    for item in items {
+        // Traits of serde can't be derived as they are, as they would bypass the states of fields.
+        if !cfg!(feature = "serde") {
+            if let Some(name) = trait_name(item.path(), SERDE_TRAITS) {
+                diagnostics.error(item.path(), format_args!("`{}` can only be implemented for builder with `serde` feature of bob enabled", name));
+                continue;
+            }
+        }
+        let name = trait_name(item.path(), &implemented);
+        match (&item, name) {
+            (Meta::Path(path), Some(name)) => derives.implemented.push(Implemented { name, path: path.clone(), bounds: None }),
+            (Meta::Path(path), None) => derives.derived.push(path.clone()),
+            (Meta::List(list), Some(name)) => {
+                let mut bounds = None;
+                for item in &attr_list(diagnostics, &item) {
+                    match *item {
+                        Meta::NameValue(ref nv) if nv.path.is_ident("bound") => {
+                            bounds = lit_str(diagnostics, "bound", &nv.value).and_then(|v| parse_predicates_value(diagnostics, "bound", &v));
+                        },
+                        Meta::NameValue(ref nv) => unknown_key(diagnostics, "builder_derive", &nv.path, &["bound"]),
+                        _ => unexpected_item(diagnostics, "builder_derive", item, "`bound = \"...\"`"),
+                    }
+                }
+                derives.implemented.push(Implemented { name, path: list.path.clone(), bounds });
+            },
+            (Meta::List(list), None) => {
+                let name = path_name(&list.path);
+                diagnostics.error(&item, format_args!("bounds can't be given for `{}`{}", name, did_you_mean(&name, &implemented, "traits")));
+                derives.derived.push(list.path.clone());
+            },
+            _ => unexpected_item(diagnostics, "builder_derive", &item, "path of a trait"),
+        }
+    }
+    derives
+}
+
+/// Gets paths of the types that are treated like `Option` given with `#[builder_option_aliases(...)]`.
+pub fn get_option_aliases(diagnostics: &Diagnostics, attrs: &[Attribute]) -> Vec<Path> {
+    attrs.iter()
+        .filter(|a| a.path().is_ident("builder_option_aliases"))
+        .flat_map(|a| attr_list(diagnostics, &a.meta))
+        .filter_map(|item| match item {
+            Meta::Path(path) => Some(path),
+            item => {
+                unexpected_item(diagnostics, "builder_option_aliases", &item, "path of a type");
+                None
+            },
+        })
+        .collect()
+}
+
+/// Default value of field given with `#[builder_default]`.
+pub struct DefaultValue {
+    /// Expression as it was given, used for documentation.
+    pub source: String,
+    pub expr: Expr,
+}
+
+impl Default for DefaultValue {
+    /// Default value that uses `Default::default()`.
+    fn default() -> DefaultValue {
+        DefaultValue {
+            source: "Default::default()".to_owned(),
+            // Use fully qualified path so that it can't be shadowed.
+            expr: parse_quote!(::std::default::Default::default()),
+        }
+    }
+}
+
+/// Gets default value for field based on attribute.
+///
+/// Plain `#[builder_default]` uses `Default::default()`, otherwise expression given as string is used.
+pub fn get_default(diagnostics: &Diagnostics, attrs: &[Attribute]) -> Option<DefaultValue> {
+    let meta = find_attr(diagnostics, attrs, "builder_default")?;
+    if let Meta::Path(_) = *meta {
+        return Some(DefaultValue::default());
+    }
+    let lit = attr_str(diagnostics, meta)?;
+    match lit.parse() {
+        Ok(expr) => Some(DefaultValue { source: lit.value(), expr }),
+        Err(_) => {
+            diagnostics.error(&lit, format_args!("`{}` given for `#[builder_default]` isn't valid expression", lit.value()));
+            None
+        },
+    }
+}
+
+/// Gets the first attribute with given name, so that errors about conflicting attributes can point at it.
+pub fn attr_named<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attrs.iter().find(|a| a.path().is_ident(name))
+}
+
+/// Checks if flag attribute like `#[builder_into]` is present.
+pub fn get_flag(diagnostics: &Diagnostics, attrs: &[Attribute], name: &str) -> bool {
+    match find_attr(diagnostics, attrs, name) {
+        Some(&Meta::Path(_)) => true,
+        Some(meta) => {
+            diagnostics.error(meta, format_args!("expected `#[{}]`", name));
+            // Attribute was still given, so it's likely that it was meant to be set.
+            true
+        },
+        None => false,
+    }
+}
+
+/// Gets name given with `#[name = "..."]` attribute like the name of the setter of field.
+pub fn get_name(diagnostics: &Diagnostics, attrs: &[Attribute], name: &str) -> Option<Ident> {
+    let meta = find_attr(diagnostics, attrs, name)?;
+    attr_str(diagnostics, meta).and_then(|v| parse_ident_value(diagnostics, name, &v))
+}
+
+/// Gets setter prefix based on attribute and falls back to default given as parameter if no attribute present.
+pub fn get_setter_prefix(diagnostics: &Diagnostics, attrs: &[Attribute], default: String) -> String {
+    find_attr(diagnostics, attrs, "builder_prefix")
+        .and_then(|meta| attr_str(diagnostics, meta))
+        .and_then(|v| if v.value().is_empty() {
+            // Empty prefix can't be parsed as identifier, but is still fine.
+            Some(String::new())
+        } else {
+            parse_ident_value(diagnostics, "builder_prefix", &v).map(|i| i.to_string())
+        })
+        .unwrap_or(default)
+}
+
+/// How builder keeps track of which required fields are set.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Type parameters of builder track the fields, so that it can be built only when all of them are set.
+    Typestate,
+    /// Builder checks that all fields are set when it's built.
+    Runtime,
+}
+
+/// Gets one of the choices given with `#[name = "..."]` attribute.
+fn get_choice(diagnostics: &Diagnostics, attrs: &[Attribute], name: &str, choices: &[&'static str], what: &str) -> Option<&'static str> {
+    let lit = find_attr(diagnostics, attrs, name).and_then(|meta| attr_str(diagnostics, meta))?;
+    let value = lit.value();
+    let choice = choices.iter().find(|&&c| c == value);
+    if choice.is_none() {
+        diagnostics.error(&lit, format_args!("unknown {} `{}`{}", what, value, did_you_mean(&value, choices, &format!("{}s", what))));
+    }
+    choice.cloned()
+}
+
+/// Gets mode of builder based on attribute and falls back to typestate if no attribute present.
+pub fn get_mode(diagnostics: &Diagnostics, attrs: &[Attribute]) -> Mode {
+    match get_choice(diagnostics, attrs, "builder_mode", &["typestate", "runtime"], "builder mode") {
+        Some("runtime") => Mode::Runtime,
+        _ => Mode::Typestate,
+    }
+}
+
+/// How setters that don't change the type of builder take it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SetterStyle {
+    /// Setters take builder by value and return it.
+    Owned,
+    /// Setters take builder by mutable reference and return the reference.
+    MutRef,
+}
+
+/// Gets style of setters based on attribute and falls back to the style preferred by the mode of builder.
+pub fn get_setter_style(diagnostics: &Diagnostics, attrs: &[Attribute], mode: Mode) -> SetterStyle {
+    match get_choice(diagnostics, attrs, "builder_setter_style", &["owned", "mut_ref"], "setter style") {
+        Some("owned") => SetterStyle::Owned,
+        Some(_) => SetterStyle::MutRef,
+        None if mode == Mode::Runtime => SetterStyle::MutRef,
+        None => SetterStyle::Owned,
+    }
+}
+
+/// Validation of the built value given with `#[builder_validate]`.
+#[derive(Clone)]
+pub struct Validation {
+    /// Function that takes the built value and returns it.
+    pub validator: Path,
+    /// If set, validator returns result instead of the value.
+    pub fallible: bool,
+    /// Functions that take reference to the built value and return result, in the order they are run.
+    pub checks: Vec<Path>,
+    /// Number of the checks that are run before the validator, as they are given before it.
+    pub checks_before: usize,
+    /// Error type of the validator and the checks, if either of them can fail.
+    pub error: Option<Path>,
+}
+
+/// Gets validator functions and error type based on attribute and falls back to default validator if there isn't one.
+///
+/// Errors of checks are boxed if error type isn't given.
+pub fn get_validation(diagnostics: &Diagnostics, attrs: &[Attribute], default: Path) -> Validation {
+    let mut validation = Validation {
+        validator: default,
+        fallible: false,
+        checks: vec![],
+        checks_before: 0,
+        error: None,
+    };
+    let meta = match find_attr(diagnostics, attrs, "builder_validate") {
+        Some(meta) => meta,
+        None => return validation,
+    };
+    let mut validator = None;
+    let mut error = None;
+    // Validator is reported missing only if it wasn't given at all.
+    let mut has_validator = false;
+    for item in &attr_list(diagnostics, meta) {
+        if let Meta::NameValue(ref nv) = *item {
+            let key = path_name(&nv.path);
+            let path = lit_str(diagnostics, &key, &nv.value).and_then(|v| parse_path_value(diagnostics, &key, &v));
+            match &key[..] {
+                "validator" => {
+                    has_validator = true;
+                    validator = path;
+                    validation.checks_before = validation.checks.len();
+                },
+                "check" => {
+                    has_validator = true;
+                    validation.checks.extend(path);
+                },
+                "error" => error = path,
+                _ => unknown_key(diagnostics, "builder_validate", &nv.path, &["validator", "check", "error"]),
+            }
+        } else {
+            unexpected_item(diagnostics, "builder_validate", item, "`key = \"path\"`");
+        }
+    }
+    if !has_validator {
+        diagnostics.error(meta, "validator or check function has to be provided for `#[builder_validate]`");
+    }
+    // Checks are run in the order they are given, so without validator they are all run before the default one.
+    match validator {
+        Some(validator) => {
+            validation.validator = validator;
+            validation.fallible = error.is_some();
+        },
+        None => validation.checks_before = validation.checks.len(),
+    }
+    if error.is_none() && !validation.checks.is_empty() {
+        error = Some(parse_quote!(::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>));
+    }
+    // Error type of validator that wasn't given isn't used.
+    if validation.fallible || !validation.checks.is_empty() {
+        validation.error = error;
+    }
+    validation
+}
+
+/// Validator of single field given with `#[builder_validate_field]`.
+pub struct FieldValidator {
+    pub validator: Path,
+    /// Type of the error, which is boxed error if it isn't given.
+    pub error: Option<Path>,
+    /// If set, validating setters are prefixed with `try_`.
+    pub try_prefix: bool,
+}
+
+/// Gets validator of field given either as `#[builder_validate_field = "path"]` or with keys in list.
+pub fn get_field_validator(diagnostics: &Diagnostics, attrs: &[Attribute]) -> Option<FieldValidator> {
+    const ATTR: &str = "builder_validate_field";
+    let meta = find_attr(diagnostics, attrs, ATTR)?;
+    if let Meta::NameValue(ref nv) = *meta {
+        let validator = lit_str(diagnostics, ATTR, &nv.value).and_then(|v| parse_path_value(diagnostics, ATTR, &v))?;
+        return Some(FieldValidator { validator, error: None, try_prefix: false });
+    }
+    let mut validator = None;
+    let mut error = None;
+    let mut try_prefix = false;
+    let mut has_validator = false;
+    for item in &attr_list(diagnostics, meta) {
+        match *item {
+            Meta::NameValue(ref nv) => {
+                let key = path_name(&nv.path);
+                let path = match &key[..] {
+                    "validator" => {
+                        has_validator = true;
+                        &mut validator
+                    },
+                    "error" => &mut error,
+                    _ => {
+                        unknown_key(diagnostics, ATTR, &nv.path, &["validator", "error", "try_prefix"]);
+                        continue;
+                    },
+                };
+                *path = lit_str(diagnostics, &key, &nv.value).and_then(|v| parse_path_value(diagnostics, &key, &v));
+            },
+            Meta::Path(ref path) if path.is_ident("try_prefix") => try_prefix = true,
+            _ => unexpected_item(diagnostics, ATTR, item, "`key = \"path\"` or `try_prefix`"),
+        }
+    }
+    if !has_validator {
+        diagnostics.error(meta, format_args!("validator function has to be provided for `#[{}]`", ATTR));
+    }
+    validator.map(|validator| FieldValidator { validator, error, try_prefix })
+}
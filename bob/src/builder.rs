@@ -0,0 +1,1418 @@
+//! Generation of the builders.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{parse_quote, Data, DeriveInput, Field, Fields, GenericArgument, GenericParam, Generics, Ident, Lifetime, Path, PathArguments, Type, TypePath};
+
+use crate::attrs::{
+    attr_named, check_attrs, get_builder_names, get_debug_style, get_default, get_derives, get_doc_comments, get_docs, get_error_name, get_field_validator, get_flag, get_mode,
+    get_name, get_option_aliases, get_option_names, get_setter_prefix, get_setter_style, get_validation, DebugStyle, DefaultValue, Derives, Diagnostics, Docs, FieldValidator, Implemented, Mode, SetterStyle, Validation,
+    FIELD_ATTRS, ITEM_ATTRS, NOT_FOR_STATES, VARIANT_ATTRS,
+};
+use crate::generics::{BuilderGenerics, State::*};
+
+/// Creates builder for struct or builders for variants of enum.
+pub fn create_builder(item: &DeriveInput) -> TokenStream {
+    let diagnostics = Diagnostics::default();
+    let name = &item.ident;
+    check_attrs(&diagnostics, &item.attrs, ITEM_ATTRS);
+    let names = get_builder_names(&diagnostics, &item.attrs, (format_ident!("Builder"), format_ident!("new"), format_ident!("build")));
+    let (ref builder, _, ref build) = names;
+    // This module holds types generated so they don't conflict with user added/generated by other invocations of this.
+    let builder_mod = format_ident!("_{}", builder.unraw().to_string().to_lowercase());
+    let validation = get_validation(&diagnostics, &item.attrs, parse_quote!(#builder_mod::id));
+    let mode = get_mode(&diagnostics, &item.attrs);
+    let error = get_error_name(&diagnostics, &item.attrs, format_ident!("{}Error", builder.unraw(), span = builder.span()));
+    let cx = Context {
+        item,
+        diagnostics: &diagnostics,
+        builder_mod: builder_mod.clone(),
+        prefix: get_setter_prefix(&diagnostics, &item.attrs, String::new()),
+        derives: get_derives(&diagnostics, &item.attrs),
+        into: get_flag(&diagnostics, &item.attrs, "builder_into"),
+        overwrite: get_flag(&diagnostics, &item.attrs, "builder_overwrite"),
+        required: get_flag(&diagnostics, &item.attrs, "builder_required"),
+        option_aliases: get_option_aliases(&diagnostics, &item.attrs),
+        mode,
+        setter_style: get_setter_style(&diagnostics, &item.attrs, mode),
+        validation,
+        error,
+        to_builder: get_flag(&diagnostics, &item.attrs, "builder_to_builder"),
+    };
+
+    let vis = &item.vis;
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    // Error for builders that check that required fields are set when building.
+    let missing_fields = if cx.mode == Mode::Runtime {
+        quote!(
+            /// Error returned when building fails because some required fields aren't set.
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            pub struct MissingFields {
+                pub(super) fields: ::std::vec::Vec<&'static str>,
+            }
+
+            impl MissingFields {
+                /// Names of the required fields that aren't set.
+                pub fn fields(&self) -> &[&'static str] {
+                    &self.fields
+                }
+            }
+
+            impl ::std::fmt::Display for MissingFields {
+                fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    write!(fmt, "required fields aren't set: {}", self.fields.join(", "))
+                }
+            }
+
+            impl ::std::error::Error for MissingFields {}
+        )
+    } else {
+        quote!()
+    };
+    let field_validation = has_field_attr(item, "builder_validate_field");
+    // Error for setters that validate their values.
+    let field_error = if field_validation {
+        quote!(
+            /// Error returned by setter when the value given for field isn't valid.
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            pub struct FieldError<E> {
+                pub(super) field: &'static str,
+                pub(super) error: E,
+            }
+
+            impl<E> FieldError<E> {
+                /// Name of the field whose value wasn't valid.
+                pub fn field(&self) -> &'static str {
+                    self.field
+                }
+
+                /// Error returned by the validator of the field.
+                pub fn error(&self) -> &E {
+                    &self.error
+                }
+
+                /// Takes the error returned by the validator of the field.
+                pub fn into_error(self) -> E {
+                    self.error
+                }
+            }
+
+            impl<E: ::std::fmt::Display> ::std::fmt::Display for FieldError<E> {
+                fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    write!(fmt, "invalid value for field `{}`: {}", self.field, self.error)
+                }
+            }
+
+            impl<E: ::std::fmt::Debug + ::std::fmt::Display> ::std::error::Error for FieldError<E> {}
+        )
+    } else {
+        quote!()
+    };
+    // Traits of builder are derived for the states too, so that derives can require them from the type parameters of the states.
+    // This includes traits with generated impls, as they can be supertraits of the derived ones.
+    // Traits that construct values aren't derived for them, so that builder can't be created with required fields in set state.
+    let Derives { ref implemented, ref derived } = cx.derives;
+    let implemented = implemented.iter()
+        .filter(|i| !NOT_FOR_STATES.contains(&i.name))
+        .map(|i| &i.path)
+        .collect::<Vec<_>>();
+    let markers_derive = if implemented.is_empty() && derived.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive(#(#implemented,)* #(#derived),*)])
+    };
+    // Placeholders are shown in debug output of builder in place of values that aren't shown.
+    let placeholders = if cx.derives.implemented("Debug").is_some() {
+        quote!(
+            pub struct Placeholder(&'static str);
+
+            impl ::std::fmt::Debug for Placeholder {
+                fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    fmt.write_str(self.0)
+                }
+            }
+
+            pub const UNSET: Placeholder = Placeholder("<unset>");
+            pub const DEFAULT: Placeholder = Placeholder("<default>");
+            pub const REDACTED: Placeholder = Placeholder("<redacted>");
+            pub const NONE: Placeholder = Placeholder("None");
+        )
+    } else {
+        quote!()
+    };
+    // Visitor is generic over the builder so that builders of all variants can use it.
+    let visitor = if let Some(implemented) = cx.derives.implemented("Deserialize") {
+        if cx.mode != Mode::Runtime {
+            diagnostics.error(&implemented.path, "`Deserialize` can only be implemented for builder in runtime mode");
+        }
+        quote!(
+            pub struct Visitor<B>(pub(super) ::std::marker::PhantomData<fn() -> B>);
+        )
+    } else {
+        quote!()
+    };
+    let markers_import = if derived.is_empty() {
+        quote!()
+    } else {
+        quote!(use super::*;)
+    };
+    let mut tks = quote!(
+        #[doc(hidden)]
+        #[allow(unused)]
+        #vis mod #builder_mod {
+            // Derives are given by their paths in the module of the item.
+            #markers_import
+            // Indicates that value isn't set
+            #markers_derive
+            pub struct O;
+            // Indicates that value is set
+            #markers_derive
+            pub struct I;
+            // This function is is used for when user doesn't provide validation function.
+            pub fn id<T>(t: T) -> T {t}
+            #missing_fields
+            #field_error
+            #placeholders
+            #visitor
+        }
+    );
+    // Error is re-exported, so that it can be named without the module of the builder.
+    if cx.mode == Mode::Runtime {
+        let missing_fields_name = format_ident!("{}MissingFields", builder.unraw(), span = builder.span());
+        tks.extend(quote!(
+            #[allow(unused_imports)]
+            #vis use #builder_mod::MissingFields as #missing_fields_name;
+        ));
+    }
+    if let Some(ref error) = cx.error {
+        tks.extend(create_error(&cx, error, field_validation));
+    }
+    match item.data {
+        Data::Struct(ref s) => {
+            let docs = get_docs(&diagnostics, &item.attrs, &["builder", "new", "build"]);
+            let (builder_tks, constructor, start_impl) = create_variant_builder(&cx, &docs, &s.fields, &quote!(#name), &name.unraw().to_string(), &names);
+            tks.extend(builder_tks);
+            tks.extend(quote!(
+                #start_impl {
+                    #constructor
+                }
+            ));
+        },
+        Data::Enum(ref e) => {
+            let docs = get_docs(&diagnostics, &item.attrs, &["builder"]);
+            if let Some(attr) = attr_named(&item.attrs, "builder_to_builder") {
+                diagnostics.error(attr, "`#[builder_to_builder]` can only be used on structs");
+            }
+            let mut constructors = vec![];
+            for variant in &e.variants {
+                let variant_name = &variant.ident;
+                let target_name = format!("{}::{}", name.unraw(), variant_name.unraw());
+                check_attrs(&diagnostics, &variant.attrs, VARIANT_ATTRS);
+                let variant_names = get_builder_names(&diagnostics, &variant.attrs, (
+                    format_ident!("{}{}", builder, variant_name),
+                    snake_case_ident(variant_name),
+                    build.clone()));
+                let variant_docs = get_docs(&diagnostics, &variant.attrs, &["builder", "new", "build"]);
+                // Per variant prefixes are supported
+                let cx = Context {
+                    prefix: get_setter_prefix(&diagnostics, &variant.attrs, cx.prefix.clone()),
+                    to_builder: false,
+                    ..cx.clone()
+                };
+                let (builder_tks, constructor, _) = create_variant_builder(&cx, &variant_docs, &variant.fields, &quote!(#name::#variant_name), &target_name, &variant_names);
+                tks.extend(builder_tks);
+                constructors.push(constructor);
+            }
+            let builder_doc = docs.apply(&docs.builder, format!("Builder for `{}`.\n\nEach variant has its own constructor that starts building it.", name.unraw()));
+            let generics = BuilderGenerics::new(&item.generics, builder, &builder_mod, 0);
+            let declaration = generics.declaration();
+            let header = generics.impl_header(None, &[], vec![]);
+            tks.extend(quote!(
+                #[doc = #builder_doc]
+                #vis struct #builder #declaration {
+                    _marker: ::std::marker::PhantomData<fn() -> #name #ty_generics>,
+                }
+
+                #header {
+                    #(#constructors)*
+                }
+            ));
+        },
+        Data::Union(ref u) => diagnostics.error(u.union_token, "builders can't be derived for unions"),
+    }
+    tks.extend(diagnostics.to_tokens());
+    tks
+}
+
+/// Creates error type that wraps every error that can happen when building.
+///
+/// Variants exist only for the errors that can happen with the given configuration.
+fn create_error(cx: &Context, error: &Ident, field_validation: bool) -> TokenStream {
+    let Context { item, ref builder_mod, ref validation, mode, .. } = *cx;
+    let vis = &item.vis;
+    let boxed = quote!(::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>);
+    let mut variants = vec![];
+    let mut conversions = vec![];
+    if field_validation {
+        variants.push((quote!(
+            /// Value given for field wasn't valid.
+            Field(#builder_mod::FieldError<#boxed>)
+        ), quote!(Field), quote!(::std::option::Option::Some(error))));
+        conversions.push(quote!(
+            impl<E: ::std::convert::Into<#boxed>> ::std::convert::From<#builder_mod::FieldError<E>> for #error {
+                fn from(error: #builder_mod::FieldError<E>) -> #error {
+                    #error::Field(#builder_mod::FieldError {
+                        field: error.field,
+                        error: error.error.into(),
+                    })
+                }
+            }
+        ));
+    }
+    if mode == Mode::Runtime {
+        variants.push((quote!(
+            /// Some of the required fields weren't set.
+            MissingFields(#builder_mod::MissingFields)
+        ), quote!(MissingFields), quote!(::std::option::Option::Some(error))));
+        conversions.push(quote!(
+            impl ::std::convert::From<#builder_mod::MissingFields> for #error {
+                fn from(error: #builder_mod::MissingFields) -> #error {
+                    #error::MissingFields(error)
+                }
+            }
+        ));
+    }
+    if let Some(ref validator_error) = validation.error {
+        variants.push((quote!(
+            /// Validator or check of the built value failed.
+            Validation(#validator_error)
+        ), quote!(Validation), quote!(::std::option::Option::None)));
+        conversions.push(quote!(
+            impl ::std::convert::From<#validator_error> for #error {
+                fn from(error: #validator_error) -> #error {
+                    #error::Validation(error)
+                }
+            }
+        ));
+    }
+    // Error without variants could never be returned, so it isn't generated.
+    if variants.is_empty() {
+        if let Some(attr) = attr_named(&item.attrs, "builder_error") {
+            cx.diagnostics.error(attr, "`#[builder_error]` requires something that can fail: runtime mode, validator with error type or field validators");
+        }
+        return quote!();
+    }
+    let names = variants.iter().map(|v| &v.1).collect::<Vec<_>>();
+    // Errors of the validator aren't required to implement Error, so only the other errors are given as sources.
+    let sources = variants.iter().map(|v| &v.2);
+    let variants = variants.iter().map(|v| &v.0);
+    let doc = format!("Error returned when building `{}` fails.", item.ident.unraw());
+    quote!(
+        #[doc = #doc]
+        #[derive(Debug)]
+        #vis enum #error {
+            #(#variants),*
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    #(#error::#names(ref error) => ::std::fmt::Display::fmt(error, fmt),)*
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {
+            #[allow(unused_variables)]
+            fn source(&self) -> ::std::option::Option<&(dyn ::std::error::Error + 'static)> {
+                match *self {
+                    #(#error::#names(ref error) => #sources,)*
+                }
+            }
+        }
+
+        #(#conversions)*
+    )
+}
+
+/// Things shared by all builders generated for one item.
+#[derive(Clone)]
+struct Context<'a> {
+    item: &'a DeriveInput,
+    diagnostics: &'a Diagnostics,
+    builder_mod: Ident,
+    prefix: String,
+    derives: Derives,
+    /// If set, all setters take anything that can be converted into the type of field.
+    into: bool,
+    /// If set, required fields can be set again.
+    overwrite: bool,
+    /// If set, `Option` fields without default value are required.
+    required: bool,
+    /// Types that are treated like `Option`.
+    option_aliases: Vec<Path>,
+    mode: Mode,
+    setter_style: SetterStyle,
+    validation: Validation,
+    /// Name of the generated error type that wraps all errors, if there is one.
+    error: Option<Ident>,
+    /// If set, built struct can be converted back into builder.
+    to_builder: bool,
+}
+
+/// Field of struct or enum variant with its attributes parsed.
+struct BuilderField<'a> {
+    /// Name of the field in builder.
+    ///
+    /// Fields need to be renamed so that they don't conflict with _marker field.
+    fname: Ident,
+    field: &'a Field,
+    /// Inner type of the field, if it's `Option`.
+    option: Option<Type>,
+    /// Name used for setter and documentation.
+    name: Ident,
+    prefix: String,
+    default: Option<DefaultValue>,
+    /// Name of the setter that appends single item to collection.
+    each: Option<Ident>,
+    /// If set, setter takes anything that can be converted into the type of field.
+    into: bool,
+    /// If set, required field can be set again.
+    overwrite: bool,
+    /// If set, field is required even if it's `Option`.
+    required: bool,
+    /// Validator that setters use to check the value.
+    validator: Option<FieldValidator>,
+    /// Names of the setters that take `Option` and clear the value, if field is optional `Option`.
+    option_names: Option<(Ident, Ident)>,
+    /// How field is shown in debug output of builder.
+    debug: DebugStyle,
+    docs: Docs,
+}
+
+impl<'a> BuilderField<'a> {
+    /// Parses attributes of field.
+    fn new(cx: &Context, i: usize, field: &'a Field) -> BuilderField<'a> {
+        let diagnostics = cx.diagnostics;
+        check_attrs(diagnostics, &field.attrs, FIELD_ATTRS);
+        let option = unwrap_from_option(&field.ty, &cx.option_aliases);
+        let each = get_name(diagnostics, &field.attrs, "builder_each");
+        let mut default = get_default(diagnostics, &field.attrs);
+        let required = get_flag(diagnostics, &field.attrs, "builder_required");
+        let optional = get_flag(diagnostics, &field.attrs, "builder_optional");
+        let validator = get_field_validator(diagnostics, &field.attrs);
+        if validator.is_some() && each.is_some() {
+            if let Some(attr) = attr_named(&field.attrs, "builder_validate_field") {
+                diagnostics.error(attr, "items appended to field can't be validated");
+            }
+        }
+        if required {
+            let conflicts = [
+                ("builder_optional", "field can't be both required and optional"),
+                ("builder_default", "required field can't have default value"),
+                ("builder_each", "items can't be appended to required field"),
+            ];
+            for &(name, msg) in &conflicts {
+                if let Some(attr) = attr_named(&field.attrs, name) {
+                    diagnostics.error(attr, msg);
+                }
+            }
+        } else if optional && default.is_none() && each.is_none() && option.is_none() {
+            if let Some(attr) = attr_named(&field.attrs, "builder_optional") {
+                diagnostics.error(attr, "optional field that isn't `Option` needs `#[builder_default]`");
+            }
+        }
+        // Explicitly required field is kept required even if other attributes conflict with it.
+        if required {
+            default = None;
+        }
+        // Collections that items are appended to are empty by default, which makes them optional.
+        if each.is_some() && default.is_none() && option.is_none() && !required {
+            default = Some(DefaultValue::default());
+        }
+        // Option fields are optional unless they are required, either by themselves or by the whole item.
+        let required = required || (cx.required && !optional && default.is_none() && each.is_none());
+        let mut field = BuilderField {
+            fname: format_ident!("_f{}", i),
+            field,
+            option,
+            // Fields of tuple structs are named by their position if there is no attribute present.
+            name: get_name(diagnostics, &field.attrs, "builder_name")
+                .or_else(|| field.ident.clone())
+                .unwrap_or_else(|| format_ident!("field_{}", i)),
+            prefix: get_setter_prefix(diagnostics, &field.attrs, cx.prefix.clone()),
+            default,
+            each,
+            into: cx.into || get_flag(diagnostics, &field.attrs, "builder_into"),
+            overwrite: cx.overwrite || get_flag(diagnostics, &field.attrs, "builder_overwrite"),
+            required,
+            validator,
+            option_names: None,
+            debug: get_debug_style(diagnostics, &field.attrs),
+            docs: get_docs(diagnostics, &field.attrs, &["setter"]),
+        };
+        if field.strips_option() {
+            let name = field.name.unraw().to_string();
+            // Both names are based on the prefixed name of the field, like the setter.
+            // Leading underscores of unused fields would end up in the middle of the name of the clearing setter.
+            let default = (
+                format_ident!("{}_opt", field.setter_name().unraw(), span = field.name.span()),
+                format_ident!("clear_{}", format!("{}{}", field.prefix, name).trim_start_matches('_'), span = field.name.span()),
+            );
+            field.option_names = Some(get_option_names(diagnostics, &field.field.attrs, default));
+        } else if let Some(attr) = attr_named(&field.field.attrs, "builder_option_names") {
+            diagnostics.error(attr, "`#[builder_option_names]` can only be used on optional `Option` fields");
+        }
+        field
+    }
+
+    /// Checks if field is optional either by being Option or by having default value.
+    ///
+    /// Fields that are explicitly required are never optional.
+    fn is_optional(&self) -> bool {
+        !self.required && (self.option.is_some() || self.default.is_some())
+    }
+
+    /// Checks if field is optional `Option` whose setter takes the inner type.
+    fn strips_option(&self) -> bool {
+        self.is_optional() && self.option.is_some()
+    }
+
+    /// Checks if field is stored in builder as it is, because it's optional `Option` with initial value of `None`.
+    ///
+    /// Otherwise it's wrapped into Option so that we know if it was set.
+    /// This includes `Option` fields with default value, so that explicitly set `None` isn't replaced by the default.
+    fn is_stored_as_is(&self) -> bool {
+        self.strips_option() && self.default.is_none()
+    }
+
+    /// Type of the field in builder.
+    fn storage_ty(&self) -> Type {
+        if self.is_stored_as_is() {
+            self.field.ty.clone()
+        } else {
+            wrap_into_option(&self.field.ty)
+        }
+    }
+
+    /// Type that setter takes.
+    ///
+    /// This being optional field doesn't mean that the setter takes optional.
+    fn setter_ty(&self) -> &Type {
+        match self.option {
+            Some(ref inner) if self.strips_option() => inner,
+            _ => &self.field.ty,
+        }
+    }
+
+    /// Name of the setter method.
+    fn setter_name(&self) -> Ident {
+        let try_prefix = match self.validator {
+            Some(FieldValidator { try_prefix: true, .. }) => "try_",
+            _ => "",
+        };
+        if self.prefix.is_empty() && try_prefix.is_empty() {
+            // Raw identifiers have to stay raw if they aren't prefixed.
+            self.name.clone()
+        } else {
+            format_ident!("{}{}{}", try_prefix, self.prefix, self.name.unraw(), span = self.name.span())
+        }
+    }
+
+    /// Type of the setters parameter and expression that converts it into given type.
+    fn setter_param<T: ToTokens>(&self, name: &Ident, ty: T) -> (TokenStream, TokenStream) {
+        if self.into {
+            (quote!(impl ::std::convert::Into<#ty>), quote!(::std::convert::Into::into(#name)))
+        } else {
+            (quote!(#ty), quote!(#name))
+        }
+    }
+
+    /// Expression that stores given value of the field into builder.
+    fn stored<T: ToTokens>(&self, value: T) -> TokenStream {
+        if self.is_stored_as_is() {
+            quote!(#value)
+        } else {
+            quote!(::std::option::Option::Some(#value))
+        }
+    }
+
+    /// Expression for mutable reference to the collection in builder, which is initialized if it wasn't set.
+    ///
+    /// Items are appended to the default value, if there is one.
+    fn collection(&self) -> TokenStream {
+        let fname = &self.fname;
+        match self.default {
+            Some(DefaultValue { ref expr, .. }) if self.strips_option() => quote!(
+                self.#fname.get_or_insert_with(|| #expr).get_or_insert_with(::std::default::Default::default)
+            ),
+            Some(DefaultValue { ref expr, .. }) => quote!(self.#fname.get_or_insert_with(|| #expr)),
+            None => quote!(self.#fname.get_or_insert_with(::std::default::Default::default)),
+        }
+    }
+
+    /// Type of the error in `FieldError` that is returned when value isn't valid, if field has validator.
+    fn validator_error(&self) -> Option<TokenStream> {
+        match self.validator {
+            Some(FieldValidator { error: Some(ref error), .. }) => Some(quote!(#error)),
+            Some(FieldValidator { error: None, .. }) => Some(quote!(::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>)),
+            None => None,
+        }
+    }
+
+    /// Expression that passes given value through the validator of field, if there is one.
+    ///
+    /// If value isn't valid, expression that returns with `FieldError` is created by given function.
+    /// If value is optional, only the value inside it is validated.
+    fn validated<F>(&self, builder_mod: &Ident, value: TokenStream, optional: bool, fail: F) -> TokenStream
+    where F: Fn(TokenStream) -> TokenStream {
+        let (validator, error) = match (&self.validator, self.validator_error()) {
+            (Some(validator), Some(error)) => (&validator.validator, error),
+            _ => return value,
+        };
+        let field = self.name.unraw().to_string();
+        let failed = fail(quote!(#builder_mod::FieldError::<#error> {
+            field: #field,
+            error: ::std::convert::From::from(error),
+        }));
+        let check = |value: TokenStream| quote!(
+            match #validator(#value) {
+                ::std::result::Result::Ok(value) => value,
+                ::std::result::Result::Err(error) => #failed,
+            }
+        );
+        if optional {
+            let check = check(quote!(value));
+            quote!(
+                match #value {
+                    ::std::option::Option::Some(value) => ::std::option::Option::Some(#check),
+                    ::std::option::Option::None => ::std::option::Option::None,
+                }
+            )
+        } else {
+            check(value)
+        }
+    }
+
+    /// Return type and body of setter that stores given value with given function.
+    ///
+    /// If field has validator, value is validated before it's stored and setter returns result.
+    /// Value given to the function is then bound to the name of the field.
+    /// If value is optional, only the value inside it is validated.
+    fn setter_body<F>(&self, builder_mod: &Ident, returned: TokenStream, value: TokenStream, optional: bool, store: F) -> (TokenStream, TokenStream)
+    where F: FnOnce(TokenStream) -> TokenStream {
+        let error = match self.validator_error() {
+            Some(error) => error,
+            None => return (returned, store(value)),
+        };
+        let name = &self.name;
+        let checked = self.validated(builder_mod, value, optional, |error| quote!(return ::std::result::Result::Err(#error)));
+        let stored = store(quote!(#name));
+        (
+            quote!(::std::result::Result<#returned, #builder_mod::FieldError<#error>>),
+            quote!(
+                let #name = #checked;
+                ::std::result::Result::Ok(#stored)
+            ),
+        )
+    }
+
+    /// Expression for reference to the value shown in debug output of builder.
+    ///
+    /// Required fields that aren't set are shown as unset and optional ones as default, so that the states of fields can be seen.
+    fn debug_value(&self, builder_mod: &Ident) -> TokenStream {
+        let fname = &self.fname;
+        let unset = if self.is_optional() {
+            quote!(#builder_mod::DEFAULT)
+        } else {
+            quote!(#builder_mod::UNSET)
+        };
+        match (self.debug, self.is_stored_as_is()) {
+            (DebugStyle::Redact, true) => quote!(
+                match self.#fname {
+                    ::std::option::Option::Some(_) => &#builder_mod::REDACTED,
+                    ::std::option::Option::None => &#builder_mod::NONE,
+                }
+            ),
+            (DebugStyle::Redact, false) => quote!(
+                match self.#fname {
+                    ::std::option::Option::Some(_) => &#builder_mod::REDACTED,
+                    ::std::option::Option::None => &#unset,
+                }
+            ),
+            (_, true) => quote!(&self.#fname),
+            (_, false) => quote!(
+                match self.#fname {
+                    ::std::option::Option::Some(ref value) => value,
+                    ::std::option::Option::None => &#unset,
+                }
+            ),
+        }
+    }
+
+    /// Creates documentation for setter method.
+    ///
+    /// Fields doc comments are appended to the generated documentation unless it's overridden with `#[builder_docs(setter = "...")]`.
+    fn setter_doc(&self, generated: String) -> String {
+        let generated = match self.validator {
+            Some(FieldValidator { ref validator, .. }) => format!("{}\n\nValue is validated with `{}` and error is returned if it isn't valid.", generated, quote!(#validator)),
+            None => generated,
+        };
+        let comments = get_doc_comments(&self.field.attrs);
+        let generated = if comments.is_empty() {
+            generated
+        } else {
+            format!("{}\n\n{}", generated, comments.join("\n"))
+        };
+        self.docs.apply(&self.docs.setter, generated)
+    }
+}
+
+/// Creates builder for fields of struct or enum variant.
+///
+/// Items of the builder, its constructor and the start of impl block for the constructor are returned separately, because constructor is placed by the caller.
+fn create_variant_builder(cx: &Context, docs: &Docs, s: &Fields, target: &TokenStream, target_name: &str, names: &(Ident, Ident, Ident)) -> (TokenStream, TokenStream, TokenStream) {
+    let Context { item, ref builder_mod, ref derives, ref validation, ref error, mode, setter_style, .. } = *cx;
+    let Validation { ref validator, fallible, ref checks, checks_before, error: ref validator_error } = *validation;
+    let runtime = mode == Mode::Runtime;
+    let mut_ref = setter_style == SetterStyle::MutRef;
+    // Builder that is checked at runtime or modified through references is built from a reference,
+    // so it can be built multiple times and values are cloned, or consumed without cloning.
+    let by_ref = runtime || mut_ref;
+    let (ref builder, ref new, ref build) = *names;
+
+    let name = &item.ident;
+    let vis = &item.vis;
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+
+    let all_fields = s.iter()
+        .enumerate()
+        .map(|(i, f)| BuilderField::new(cx, i, f))
+        .collect::<Vec<_>>();
+    let all_field_refs = &all_fields.iter().collect::<Vec<_>>();
+    let (opt_fields, fields): (Vec<_>, Vec<_>) = all_fields.iter()
+        .partition(|f| f.is_optional());
+
+    // Required fields are wrapped into Option so that they can have initial value.
+    let builder_field_names = &fields.iter()
+        .map(|f| &f.fname)
+        .collect::<Vec<_>>();
+    let builder_field_tys = fields.iter()
+        .map(|f| f.storage_ty());
+    let builder_opt_field_names = &opt_fields.iter()
+        .map(|f| &f.fname)
+        .collect::<Vec<_>>();
+    let builder_opt_field_tys = opt_fields.iter()
+        .map(|f| f.storage_ty());
+    // Values of the built struct are taken in declaration order so that tuple structs can be constructed.
+    // They are cloned if the struct is built from a reference.
+    let result_of = |cloned: bool| {
+        let result_values = all_fields.iter()
+            .map(|f| {
+                let i = &f.fname;
+                let value = if cloned {
+                    quote!(self.#i.clone())
+                } else {
+                    quote!(self.#i)
+                };
+                if !f.is_optional() {
+                    return if runtime {
+                        quote!(#value.expect("Missing fields should have been checked before building."))
+                    } else {
+                        quote!(#value.expect("Type parameters should ensure that there is always value."))
+                    };
+                }
+                // Optional fields that weren't set are filled with their default values.
+                match f.default {
+                    Some(DefaultValue { ref expr, .. }) => quote!(#value.unwrap_or_else(|| #expr)),
+                    None => value,
+                }
+            })
+            .collect::<Vec<_>>();
+        match *s {
+            Fields::Named(ref fields) => {
+                let result_fields = fields.named.iter().map(|f| &f.ident);
+                quote!(#target { #(#result_fields: #result_values),* })
+            },
+            Fields::Unnamed(_) => quote!(#target(#(#result_values),*)),
+            Fields::Unit => quote!(#target),
+        }
+    };
+
+    // Builder that is checked at runtime doesn't track the states of the fields.
+    let generics = BuilderGenerics::new(&item.generics, builder, builder_mod, if runtime { 0 } else { fields.len() });
+    let declaration = generics.declaration();
+    // Type parameters for builders required fields
+    let builder_ty_params = generics.state_params();
+    // Bounds of generated impls of traits require the types of fields to implement the trait, unless bounds are given.
+    // Types that don't depend on the parameters of the built item are left out, as they don't need to be required.
+    let bounds = |implemented: &Implemented, bound: TokenStream, fields: &[&BuilderField]| match implemented.bounds {
+        Some(ref bounds) => bounds.clone(),
+        None => fields.iter()
+            .map(|f| &f.field.ty)
+            .filter(|ty| uses_params(ty, &item.generics))
+            .map(|ty| parse_quote!(#ty: #bound))
+            .collect::<Vec<_>>(),
+    };
+
+    let required = if fields.is_empty() {
+        "".into()
+    } else {
+        fields.iter()
+            .map(|f| format!("* `{}`\n", f.name.unraw()))
+            .fold("# Required fields\n".to_owned(), |a, b| a + &b)
+    };
+    let optional = if opt_fields.is_empty() {
+        "".into()
+    } else {
+        opt_fields.iter()
+            .map(|f| match f.default {
+                Some(ref default) => format!("* `{}` (defaults to `{}`)\n", f.name.unraw(), default.source),
+                None => format!("* `{}`\n", f.name.unraw()),
+            })
+            .fold("# Optional fields\n".to_owned(), |a, b| a + &b)
+    };
+
+    let builder_doc = docs.apply(&docs.builder, format!("Builder for `{}`.\n{}\n{}", target_name, required, optional));
+    let constructor_doc = docs.apply(&docs.new, "Constructor for builder.\n\nAll fields are unset at the start.".to_owned());
+    let build_doc = docs.apply(&docs.build, if runtime {
+        format!("Builds new `{}`.\n\nFails if some of the required fields aren't set.", target_name)
+    } else {
+        format!("Builds new `{}`.\n\nThis method is usable only if all required fields are set.", target_name)
+    });
+    let derived = &derives.derived;
+    let derive = if derived.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive(#(#derived),*)])
+    };
+    let mut tks = quote!(
+        #[doc = #builder_doc]
+        #derive
+        #vis struct #builder #declaration {
+            // Built type is included so that every lifetime and type parameter is used even if variant doesn't use it.
+            _marker: ::std::marker::PhantomData<(fn() -> #name #ty_generics, #(#builder_ty_params),*)>,
+            #(#builder_field_names: #builder_field_tys,)*
+            #(#builder_opt_field_names: #builder_opt_field_tys),*
+        }
+    );
+    // Constructor is placed by the caller as enums have constructors for all variants in one place.
+    // At the start builder doesn't have any values set.
+    let start_ty = generics.ty(&generics.all(Unset));
+    let start = quote!(
+        #builder {
+            _marker: ::std::marker::PhantomData,
+            #(#builder_field_names: ::std::option::Option::None,)*
+            #(#builder_opt_field_names: ::std::option::Option::None),*
+        }
+    );
+    let constructor = quote!(
+        #[doc = #constructor_doc]
+        #vis fn #new() -> #start_ty {
+            #start
+        }
+    );
+
+    if let Some(implemented) = derives.implemented("Clone") {
+        let header = generics.impl_header(Some(quote!(::std::clone::Clone)), &generics.all(Any), bounds(implemented, quote!(::std::clone::Clone), all_field_refs));
+        tks.extend(quote!(
+            #header {
+                fn clone(&self) -> Self {
+                    #builder {
+                        _marker: ::std::marker::PhantomData,
+                        #(#builder_field_names: self.#builder_field_names.clone(),)*
+                        #(#builder_opt_field_names: self.#builder_opt_field_names.clone()),*
+                    }
+                }
+            }
+        ));
+    }
+
+    if let Some(implemented) = derives.implemented("Debug") {
+        // Fields are shown with their names in declaration order and skipped fields are left out.
+        // Values of redacted or skipped fields aren't required to implement Debug.
+        let shown = all_fields.iter()
+            .filter(|f| f.debug != DebugStyle::Skip)
+            .collect::<Vec<_>>();
+        let header = generics.impl_header(Some(quote!(::std::fmt::Debug)), &generics.all(Any), bounds(implemented, quote!(::std::fmt::Debug), &shown.iter()
+            .filter(|f| f.debug == DebugStyle::Show)
+            .cloned()
+            .collect::<Vec<_>>()));
+        let builder_name = builder.unraw().to_string();
+        let debug_names = shown.iter().map(|f| f.name.unraw().to_string());
+        let debug_values = shown.iter().map(|f| f.debug_value(builder_mod));
+        let finish = if shown.len() < all_fields.len() {
+            quote!(finish_non_exhaustive)
+        } else {
+            quote!(finish)
+        };
+        tks.extend(quote!(
+            #header {
+                fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    fmt.debug_struct(#builder_name)
+                        #(.field(#debug_names, #debug_values))*
+                        .#finish()
+                }
+            }
+        ));
+    }
+
+    let all_field_names = &builder_field_names.iter()
+        .chain(builder_opt_field_names)
+        .collect::<Vec<_>>();
+    if let Some(implemented) = derives.implemented("PartialEq") {
+        let header = generics.impl_header(Some(quote!(::std::cmp::PartialEq)), &generics.all(Any), bounds(implemented, quote!(::std::cmp::PartialEq), all_field_refs));
+        tks.extend(quote!(
+            #header {
+                fn eq(&self, other: &Self) -> bool {
+                    true #(&& self.#all_field_names == other.#all_field_names)*
+                }
+            }
+        ));
+    }
+
+    if let Some(implemented) = derives.implemented("Eq") {
+        let header = generics.impl_header(Some(quote!(::std::cmp::Eq)), &generics.all(Any), bounds(implemented, quote!(::std::cmp::Eq), all_field_refs));
+        tks.extend(quote!(
+            #header {}
+        ));
+    }
+
+    if let Some(implemented) = derives.implemented("Hash") {
+        let header = generics.impl_header(Some(quote!(::std::hash::Hash)), &generics.all(Any), bounds(implemented, quote!(::std::hash::Hash), all_field_refs));
+        tks.extend(quote!(
+            #header {
+                fn hash<_H: ::std::hash::Hasher>(&self, state: &mut _H) {
+                    #(::std::hash::Hash::hash(&self.#all_field_names, state);)*
+                }
+            }
+        ));
+    }
+
+    // Only builder that doesn't have any fields set can be created without values.
+    if let Some(implemented) = derives.implemented("Default") {
+        let header = generics.impl_header(Some(quote!(::std::default::Default)), &generics.all(Unset), implemented.bounds.clone().unwrap_or_default());
+        tks.extend(quote!(
+            #header {
+                fn default() -> Self {
+                    #start
+                }
+            }
+        ));
+    }
+
+    // Only the fields that are set are serialized, so that the builder can be deserialized back.
+    if let Some(implemented) = derives.implemented("Serialize") {
+        let header = generics.impl_header(Some(quote!(::serde::Serialize)), &generics.all(Any), bounds(implemented, quote!(::serde::Serialize), all_field_refs));
+        let builder_name = builder.unraw().to_string();
+        let serialized = all_fields.iter()
+            .map(|f| {
+                let fname = &f.fname;
+                let name = f.name.unraw().to_string();
+                // Optional `Option` is serialized as it is, so that it's deserialized the same way as other fields.
+                if f.is_stored_as_is() {
+                    quote!(
+                        if self.#fname.is_some() {
+                            ::serde::ser::SerializeStruct::serialize_field(&mut state, #name, &self.#fname)?;
+                        }
+                    )
+                } else {
+                    quote!(
+                        if let ::std::option::Option::Some(ref value) = self.#fname {
+                            ::serde::ser::SerializeStruct::serialize_field(&mut state, #name, value)?;
+                        }
+                    )
+                }
+            });
+        tks.extend(quote!(
+            #header {
+                fn serialize<_S: ::serde::Serializer>(&self, serializer: _S) -> ::std::result::Result<_S::Ok, _S::Error> {
+                    let len = [#(self.#all_field_names.is_some()),*].iter().filter(|&&set| set).count();
+                    #[allow(unused_mut)]
+                    let mut state = ::serde::Serializer::serialize_struct(serializer, #builder_name, len)?;
+                    #(#serialized)*
+                    ::serde::ser::SerializeStruct::end(state)
+                }
+            }
+        ));
+    }
+
+    // Fields that are missing are left unset, so that they can be set before building.
+    // Values are passed through the validators of the fields and unknown or duplicate fields are reported as errors.
+    if let (Some(implemented), true) = (derives.implemented("Deserialize"), runtime) {
+        // Lifetime of the deserialized data is renamed if the item already has lifetime with the same name.
+        let mut de = "'de".to_owned();
+        while item.generics.lifetimes().any(|l| l.lifetime.ident == de[1..]) {
+            de.insert(1, '_');
+        }
+        let de = Lifetime::new(&de, Span::call_site());
+        let builder_ty = generics.ty(&generics.all(Any));
+        let builder_name = builder.unraw().to_string();
+        let expecting = format!("builder for `{}`", target_name);
+        // Deserialized data has to outlive the lifetimes of the built item, as borrowed values can be deserialized from it.
+        let mut predicates = bounds(implemented, quote!(::serde::Deserialize<#de>), all_field_refs);
+        predicates.extend(item.generics.lifetimes().map(|l| {
+            let lifetime = &l.lifetime;
+            parse_quote!(#de: #lifetime)
+        }));
+        let header = generics.impl_header_for(Some(&de), Some(quote!(::serde::Deserialize<#de>)), builder_ty.clone(), &generics.all(Any), predicates.clone());
+        let visitor_header = generics.impl_header_for(Some(&de), Some(quote!(::serde::de::Visitor<#de>)), quote!(#builder_mod::Visitor<#builder_ty>), &generics.all(Any), predicates);
+        let names = all_fields.iter()
+            .map(|f| f.name.unraw().to_string())
+            .collect::<Vec<_>>();
+        // Fields that have been seen are tracked separately, as optional `Option` looks unset after `None` is deserialized.
+        let count = all_fields.len();
+        let deserialized = all_fields.iter()
+            .zip(&names)
+            .enumerate()
+            .map(|(i, (f, name))| {
+                let fname = &f.fname;
+                let ty = &f.field.ty;
+                let validated = f.validated(builder_mod, quote!(value), f.strips_option(), |error| quote!(
+                    return ::std::result::Result::Err(::serde::de::Error::custom(#error))
+                ));
+                let stored = f.stored(validated);
+                quote!(
+                    #name => {
+                        if seen[#i] {
+                            return ::std::result::Result::Err(::serde::de::Error::duplicate_field(#name));
+                        }
+                        seen[#i] = true;
+                        let value: #ty = ::serde::de::MapAccess::next_value(&mut map)?;
+                        builder.#fname = #stored;
+                    },
+                )
+            });
+        let fields = quote!(const FIELDS: &[&str] = &[#(#names),*];);
+        tks.extend(quote!(
+            #visitor_header {
+                type Value = #builder_ty;
+
+                fn expecting(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    fmt.write_str(#expecting)
+                }
+
+                fn visit_map<_A: ::serde::de::MapAccess<#de>>(self, mut map: _A) -> ::std::result::Result<Self::Value, _A::Error> {
+                    #fields
+                    #[allow(unused_mut)]
+                    let mut builder = #start;
+                    #[allow(unused_mut, unused_variables)]
+                    let mut seen = [false; #count];
+                    while let ::std::option::Option::Some(key) = ::serde::de::MapAccess::next_key::<::std::string::String>(&mut map)? {
+                        match &key[..] {
+                            #(#deserialized)*
+                            _ => return ::std::result::Result::Err(::serde::de::Error::unknown_field(&key, FIELDS)),
+                        }
+                    }
+                    ::std::result::Result::Ok(builder)
+                }
+            }
+
+            #header {
+                fn deserialize<_D: ::serde::Deserializer<#de>>(deserializer: _D) -> ::std::result::Result<Self, _D::Error> {
+                    #fields
+                    ::serde::Deserializer::deserialize_struct(deserializer, #builder_name, FIELDS, #builder_mod::Visitor(::std::marker::PhantomData))
+                }
+            }
+        ));
+    }
+
+    // Every field of the built struct is stored as set, so that explicitly given values aren't replaced by defaults.
+    if cx.to_builder {
+        let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+        let set_ty = generics.ty(&generics.all(Set));
+        let fnames = all_fields.iter().map(|f| &f.fname);
+        let values = s.members()
+            .zip(&all_fields)
+            .map(|(member, f)| f.stored(quote!(self.#member)));
+        let into_builder_doc = format!("Converts `{}` into builder that has all fields set to its values.", target_name);
+        let to_builder_doc = format!("Creates builder that has all fields set to the clones of the values of `{}`.", target_name);
+        tks.extend(quote!(
+            impl #impl_generics #name #ty_generics #where_clause {
+                #[doc = #into_builder_doc]
+                #vis fn into_builder(self) -> #set_ty {
+                    #builder {
+                        _marker: ::std::marker::PhantomData,
+                        #(#fnames: #values),*
+                    }
+                }
+
+                // Bound is higher-ranked so that it's checked only when the method is used,
+                // which allows the struct to not implement Clone.
+                #[doc = #to_builder_doc]
+                #vis fn to_builder(&self) -> #set_ty where for<'_a> Self: ::std::clone::Clone {
+                    ::std::clone::Clone::clone(self).into_builder()
+                }
+            }
+        ));
+    }
+
+    // Values are cloned only if builder is built from a reference.
+    // Bounds of types that don't depend on the parameters of the built item are higher-ranked,
+    // so that they are checked only when building and fields that don't implement Clone don't prevent generating the builder.
+    let clone_bounds = if by_ref {
+        all_fields.iter()
+            .map(|f| {
+                let ty = f.storage_ty();
+                if uses_params(&f.field.ty, &item.generics) {
+                    parse_quote!(#ty: ::std::clone::Clone)
+                } else {
+                    parse_quote!(for<'_a> #ty: ::std::clone::Clone)
+                }
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    // Checks and the validator are run in the order they are given and the first error is returned.
+    // Errors are converted into the error of the validator and then into generated error, if there is one.
+    let checks = checks.iter()
+        .map(|check| quote!(
+            if let ::std::result::Result::Err(error) = #check(&value) {
+                return ::std::result::Result::Err(::std::convert::From::from(<#validator_error as ::std::convert::From<_>>::from(error)));
+            }
+        ))
+        .collect::<Vec<_>>();
+    let (checks_before, checks_after) = checks.split_at(checks_before);
+    let validated = &if fallible {
+        quote!(#validator(value)?)
+    } else {
+        quote!(#validator(value))
+    };
+    let validate = |result: TokenStream| quote!({
+        let value = #result;
+        #(#checks_before)*
+        let value = #validated;
+        #(#checks_after)*
+        ::std::result::Result::Ok(value)
+    });
+    // Builder that is built from a reference can also be consumed when building,
+    // so that values are moved out of it and don't need to implement Clone.
+    let into_build = format_ident!("into_{}", build.unraw(), span = build.span());
+    let into_build_doc = format!("{}\n\nValues are moved out of builder, so they don't need to implement `Clone`.", if runtime {
+        format!("Builds new `{}` consuming builder.\n\nFails if some of the required fields aren't set.", target_name)
+    } else {
+        format!("Builds new `{}` consuming builder.\n\nThis method is usable only if all required fields are set.", target_name)
+    });
+    let builds = if by_ref {
+        vec![(quote!(&self), build, build_doc, result_of(true), clone_bounds), (quote!(self), &into_build, into_build_doc, result_of(false), vec![])]
+    } else {
+        vec![(quote!(self), build, build_doc, result_of(false), vec![])]
+    };
+    for (receiver, build, build_doc, result, clone_bounds) in builds {
+        if runtime {
+            let required_names = fields.iter()
+                .map(|f| f.name.unraw().to_string());
+            // Missing fields are converted into validators error or generated error so that both can be returned.
+            let result_ty = match (error.as_ref(), validator_error.as_ref()) {
+                (Some(error), _) => quote!(#error),
+                (None, Some(error)) => quote!(#error),
+                (None, None) => quote!(#builder_mod::MissingFields),
+            };
+            let validated = validate(result);
+            let header = generics.impl_header(None, &[], clone_bounds);
+            tks.extend(quote!(
+                #header {
+                    #[doc = #build_doc]
+                    #vis fn #build(#receiver) -> ::std::result::Result<#name #ty_generics, #result_ty> {
+                        let mut missing = ::std::vec::Vec::new();
+                        #(
+                            if self.#builder_field_names.is_none() {
+                                missing.push(#required_names);
+                            }
+                        )*
+                        if !missing.is_empty() {
+                            return ::std::result::Result::Err(::std::convert::From::from(#builder_mod::MissingFields { fields: missing }));
+                        }
+                        #validated
+                    }
+                }
+            ));
+        } else {
+            // Building can fail only if validator or checks can.
+            let (result_ty, built) = match *validator_error {
+                Some(ref validator_error) => {
+                    let error = error.as_ref().map_or_else(|| quote!(#validator_error), |e| quote!(#e));
+                    (quote!(::std::result::Result<#name #ty_generics, #error>), validate(result))
+                },
+                None => (quote!(#name #ty_generics), quote!(#validator(#result))),
+            };
+            // When building we require that every required value is set.
+            let header = generics.impl_header(None, &generics.all(Set), clone_bounds);
+            tks.extend(quote!(
+                #header {
+                    #[doc = #build_doc]
+                    #vis fn #build(#receiver) -> #result_ty {
+                        #built
+                    }
+                }
+            ));
+        }
+    }
+
+    // Setters that don't change the type of the builder take it either by value or by mutable reference.
+    let any_ty = generics.ty(&generics.all(Any));
+    let (receiver, returned) = if mut_ref {
+        (quote!(&mut self), quote!(&mut Self))
+    } else {
+        (quote!(mut self), any_ty)
+    };
+    // If required fields aren't tracked by type parameters, their setters are the same as setters of optional fields.
+    let unchecked_fields = if runtime {
+        all_fields.iter().collect()
+    } else {
+        opt_fields.clone()
+    };
+    for field in unchecked_fields {
+        let fname = &field.fname;
+        let ty = field.setter_ty();
+        let raw_name = &field.name;
+        let name = field.setter_name();
+
+        let (param_ty, value) = field.setter_param(raw_name, ty);
+        let (setter_ty, body) = field.setter_body(builder_mod, returned.clone(), value, false, |value| {
+            let value = if field.strips_option() {
+                field.stored(quote!(::std::option::Option::Some(#value)))
+            } else {
+                field.stored(value)
+            };
+            quote!({
+                self.#fname = #value;
+                self
+            })
+        });
+
+        let setter_doc = if field.is_optional() {
+            field.setter_doc(format!("Setter method for **optional** field `{}`.", raw_name.unraw()))
+        } else {
+            field.setter_doc(format!("Setter method for **required** field `{}`.", raw_name.unraw()))
+        };
+        let header = generics.impl_header(None, &generics.all(Any), vec![]);
+        // Setter for the whole collection is replaced if setter for its items has the same name.
+        if field.each.as_ref() != Some(&name) {
+            tks.extend(quote!(
+                #header {
+                    #[doc = #setter_doc]
+                    #vis fn #name(#receiver, #raw_name: #param_ty) -> #setter_ty {
+                        #body
+                    }
+                }
+            ));
+        }
+
+        if let Some((ref opt, ref clear)) = field.option_names {
+            let (opt_ty, opt_value) = field.setter_param(raw_name, &field.field.ty);
+            let (opt_setter_ty, opt_body) = field.setter_body(builder_mod, returned.clone(), opt_value, true, |value| {
+                let value = field.stored(value);
+                quote!({
+                    self.#fname = #value;
+                    self
+                })
+            });
+            let none = field.stored(quote!(::std::option::Option::None));
+            let opt_doc = format!("Setter method for **optional** field `{}` that takes `Option`.", raw_name.unraw());
+            let clear_doc = format!("Sets the field `{}` to `None`.", raw_name.unraw());
+            tks.extend(quote!(
+                #header {
+                    #[doc = #opt_doc]
+                    #vis fn #opt(#receiver, #raw_name: #opt_ty) -> #opt_setter_ty {
+                        #opt_body
+                    }
+
+                    #[doc = #clear_doc]
+                    #vis fn #clear(#receiver) -> #returned {
+                        self.#fname = #none;
+                        self
+                    }
+                }
+            ));
+        }
+
+        if let Some(ref each) = field.each {
+            let item = quote!(<#ty as ::std::iter::IntoIterator>::Item);
+            let (item_ty, item_value) = field.setter_param(each, &item);
+            let extend = format_ident!("extend_{}", raw_name.unraw(), span = raw_name.span());
+            let collection = field.collection();
+            let bounds = vec![
+                parse_quote!(#ty: ::std::default::Default + ::std::iter::IntoIterator + ::std::iter::Extend<#item>),
+            ];
+            let header = generics.impl_header(None, &generics.all(Any), bounds);
+            let each_doc = format!("Appends item to the field `{}`.", raw_name.unraw());
+            let extend_doc = format!("Appends all items from iterator to the field `{}`.", raw_name.unraw());
+            tks.extend(quote!(
+                #header {
+                    #[doc = #each_doc]
+                    #vis fn #each(#receiver, #each: #item_ty) -> #returned {
+                        ::std::iter::Extend::extend(#collection, ::std::iter::once(#item_value));
+                        self
+                    }
+
+                    #[doc = #extend_doc]
+                    #vis fn #extend<_I: ::std::iter::IntoIterator<Item = #item>>(#receiver, #raw_name: _I) -> #returned {
+                        ::std::iter::Extend::extend(#collection, #raw_name);
+                        self
+                    }
+                }
+            ));
+        }
+    }
+
+    // Otherwise setters of required fields change the type parameter of the field.
+    let checked_fields = if runtime {
+        vec![]
+    } else {
+        fields.clone()
+    };
+    for (i, field) in checked_fields.into_iter().enumerate() {
+        let fname = &field.fname;
+        let ty = field.setter_ty();
+
+        // All but the field that the setter is setting needs to be just taken from self.
+        let mut builder_field_names = builder_field_names.clone();
+        builder_field_names.remove(i);
+        let builder_field_names = &builder_field_names;
+
+        let raw_name = &field.name;
+        let name = field.setter_name();
+
+        // Fields can be set only once unless they can be overwritten, so we require that field wasn't set before.
+        let mut states = generics.all(Any);
+        if !field.overwrite {
+            states[i] = Unset;
+        }
+        let header = generics.impl_header(None, &states, vec![]);
+        // After setting field, type parameter is changed to indicate that.
+        states[i] = Set;
+        let after_set_ty = generics.ty(&states);
+
+        let (param_ty, value) = field.setter_param(raw_name, ty);
+        let (setter_ty, body) = field.setter_body(builder_mod, after_set_ty, value, false, |value| quote!(
+            #builder {
+                _marker: ::std::marker::PhantomData,
+                #fname: ::std::option::Option::Some(#value),
+                #(#builder_field_names: self.#builder_field_names,)*
+                #(#builder_opt_field_names: self.#builder_opt_field_names),*
+            }
+        ));
+
+        let setter_doc = field.setter_doc(format!("Setter method for **required** field `{}`.", raw_name.unraw()));
+        tks.extend(quote!(
+            #header {
+                #[doc = #setter_doc]
+                #vis fn #name(self, #raw_name: #param_ty) -> #setter_ty {
+                    #body
+                }
+            }
+        ));
+    }
+    (tks, constructor, generics.impl_header(None, &generics.all(Unset), vec![]))
+}
+
+/// Checks if any field of the struct or enum has attribute with given name.
+fn has_field_attr(item: &DeriveInput, name: &str) -> bool {
+    let fields = match item.data {
+        Data::Struct(ref s) => s.fields.iter().collect(),
+        Data::Enum(ref e) => e.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(_) => vec![],
+    };
+    fields.into_iter().any(|f: &Field| attr_named(&f.attrs, name).is_some())
+}
+
+/// Checks if type refers to type or const parameters of given generics.
+fn uses_params(ty: &Type, generics: &Generics) -> bool {
+    fn contains(tokens: TokenStream, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|t| match t {
+            TokenTree::Ident(ref i) => params.contains(&i),
+            TokenTree::Group(ref g) => contains(g.stream(), params),
+            _ => false,
+        })
+    }
+    let params = generics.params.iter()
+        .filter_map(|p| match *p {
+            GenericParam::Type(ref t) => Some(&t.ident),
+            GenericParam::Const(ref c) => Some(&c.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect::<Vec<_>>();
+    contains(ty.to_token_stream(), &params)
+}
+
+/// Wraps type T into Option<T>
+fn wrap_into_option(ty: &Type) -> Type {
+    parse_quote!(::std::option::Option<#ty>)
+}
+
+/// Returns inner type T of Option<T> or None if type wasn't Option.
+///
+/// Option is recognized by its path in the prelude, `std` or `core`, or by given aliases of it.
+fn unwrap_from_option(ty: &Type, aliases: &[Path]) -> Option<Type> {
+    let path = match *ty {
+        Type::Path(TypePath { qself: None, ref path }) => path,
+        // Types passed through declarative macros can be wrapped into invisible groups.
+        Type::Group(ref g) => return unwrap_from_option(&g.elem, aliases),
+        Type::Paren(ref p) => return unwrap_from_option(&p.elem, aliases),
+        _ => return None,
+    };
+    let segments = path.segments.iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>();
+    let segments = segments.iter()
+        .map(|s| &s[..])
+        .collect::<Vec<_>>();
+    let is_std = match segments[..] {
+        ["Option"] => path.leading_colon.is_none(),
+        ["std", "option", "Option"] | ["core", "option", "Option"] => true,
+        _ => false,
+    };
+    if is_std {
+        if let PathArguments::AngleBracketed(ref a) = path.segments.last()?.arguments {
+            if let Some(GenericArgument::Type(ty)) = a.args.first() {
+                return Some(ty.clone());
+            }
+        }
+        return None;
+    }
+    let is_alias = aliases.iter()
+        .any(|a| a.segments.iter().map(|s| s.ident.to_string()).eq(segments.iter().map(|s| s.to_string())));
+    if is_alias {
+        // Arguments of the alias don't tell what the inner type is, but Option iterates over it.
+        Some(parse_quote!(<#ty as ::std::iter::IntoIterator>::Item))
+    } else {
+        None
+    }
+}
+
+/// Converts CamelCase identifier into snake_case one, which is made raw if it's a keyword.
+///
+/// Keywords that can't be raw identifiers get trailing underscore instead.
+fn snake_case_ident(ident: &Ident) -> Ident {
+    let name = to_snake_case(&ident.unraw().to_string());
+    match &name[..] {
+        "self" | "super" | "crate" => format_ident!("{}_", name, span = ident.span()),
+        _ if syn::parse_str::<Ident>(&name).is_err() => Ident::new_raw(&name, ident.span()),
+        _ => Ident::new(&name, ident.span()),
+    }
+}
+
+/// Converts CamelCase name into snake_case.
+///
+/// Run of capitals is kept as one word (`HTTPServer` becomes `http_server` and `IPv4Addr` becomes `ipv4_addr`),
+/// but its last capital starts new word if it's followed by lowercase word.
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let is_lowercase = |i: usize| chars.get(i).is_some_and(|c| c.is_lowercase());
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let starts_word = i != 0 && chars[i - 1] != '_' && (!chars[i - 1].is_uppercase() || is_lowercase(i + 1) && is_lowercase(i + 2));
+            if starts_word {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
@@ -0,0 +1,126 @@
+//! Propagation of the generics of the built item to the items generated for the builder.
+//!
+//! Every generated item gets its generics from here so that the parameters, their bounds and defaults,
+//! and the where clause of the built item are carried over the same way everywhere.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{GenericParam, Generics, Ident, Lifetime, LifetimeParam, WherePredicate};
+
+/// State of required field in the type of builder.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Field can be in either state, so type parameter is used.
+    Any,
+    /// Field isn't set.
+    Unset,
+    /// Field is set.
+    Set,
+}
+
+/// Generics of builder, which are the generics of the built item with type parameter for state of each required field.
+///
+/// Parameters for the states are placed after the lifetimes, but before the other parameters,
+/// so that defaults of the built item's parameters can be kept.
+pub struct BuilderGenerics<'a> {
+    generics: &'a Generics,
+    builder: &'a Ident,
+    builder_mod: &'a Ident,
+    states: Vec<Ident>,
+}
+
+impl<'a> BuilderGenerics<'a> {
+    /// Creates generics for builder that tracks given amount of required fields.
+    pub fn new(generics: &'a Generics, builder: &'a Ident, builder_mod: &'a Ident, required: usize) -> BuilderGenerics<'a> {
+        BuilderGenerics {
+            generics,
+            builder,
+            builder_mod,
+            states: (0..required)
+                .map(|i| format_ident!("_{}", i))
+                .collect(),
+        }
+    }
+
+    /// Type parameters for the states of required fields.
+    pub fn state_params(&self) -> &[Ident] {
+        &self.states
+    }
+
+    /// Generics with states given as `Any` added as type parameters.
+    fn with_params(&self, states: &[State]) -> Generics {
+        let mut generics = self.generics.clone();
+        let start = generics.lifetimes().count();
+        let params = self.states.iter()
+            .zip(states)
+            .filter(|&(_, &s)| s == State::Any)
+            .map(|(p, _)| GenericParam::Type(p.clone().into()));
+        for (i, param) in params.enumerate() {
+            generics.params.insert(start + i, param);
+        }
+        generics
+    }
+
+    /// Parameters and where clause used in declaration of the builder.
+    ///
+    /// Bounds and defaults of the parameters are kept.
+    pub fn declaration(&self) -> TokenStream {
+        let generics = self.with_params(&self.all(State::Any));
+        let where_clause = &generics.where_clause;
+        quote!(#generics #where_clause)
+    }
+
+    /// Every required field in given state.
+    pub fn all(&self, state: State) -> Vec<State> {
+        vec![state; self.states.len()]
+    }
+
+    /// Type of the builder where required fields are in given states.
+    pub fn ty(&self, states: &[State]) -> TokenStream {
+        let builder_mod = self.builder_mod;
+        let lifetimes = self.generics.lifetimes()
+            .map(|l| l.lifetime.to_token_stream());
+        let states = self.states.iter()
+            .zip(states)
+            .map(|(p, s)| match *s {
+                State::Any => p.to_token_stream(),
+                State::Unset => quote!(#builder_mod::O),
+                State::Set => quote!(#builder_mod::I),
+            });
+        let others = self.generics.params.iter()
+            .filter_map(|p| match *p {
+                GenericParam::Type(ref t) => Some(t.ident.to_token_stream()),
+                GenericParam::Const(ref c) => Some(c.ident.to_token_stream()),
+                GenericParam::Lifetime(_) => None,
+            });
+        let args = lifetimes.chain(states).chain(others).collect::<Vec<_>>();
+        let builder = self.builder;
+        if args.is_empty() {
+            quote!(#builder)
+        } else {
+            quote!(#builder<#(#args),*>)
+        }
+    }
+
+    /// Start of impl block for builder where required fields are in given states.
+    ///
+    /// If trait is given, it's implemented for the builder.
+    /// Given predicates are added to the where clause of the built item.
+    pub fn impl_header(&self, trait_: Option<TokenStream>, states: &[State], predicates: Vec<WherePredicate>) -> TokenStream {
+        self.impl_header_for(None, trait_, self.ty(states), states, predicates)
+    }
+
+    /// Start of impl block for given type that contains builder where required fields are in given states.
+    ///
+    /// If lifetime is given, it's added as the first parameter of the impl so that the trait can use it.
+    pub fn impl_header_for(&self, lifetime: Option<&Lifetime>, trait_: Option<TokenStream>, ty: TokenStream, states: &[State], predicates: Vec<WherePredicate>) -> TokenStream {
+        let mut generics = self.with_params(states);
+        if let Some(lifetime) = lifetime {
+            generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+        }
+        generics.make_where_clause().predicates.extend(predicates);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let trait_ = trait_.map(|t| quote!(#t for));
+        quote!(impl #impl_generics #trait_ #ty #where_clause)
+    }
+}
@@ -1,5 +1,3 @@
-#![recursion_limit = "1024"]
-
 //! # Bob the builder builder
 //! Bob provides custom derive for generating builder for struct.
 //!
@@ -22,12 +20,12 @@
 //!
 //! fn main() {
 //!     let my_struct = Builder::new()
-//!         .magic(vec![42, 7, 3]) // This line is required. Removing it gives error.
+//!         .magics(vec![42, 7, 3]) // This line is required. Removing it gives error.
 //!         .greeting("Potato".to_owned()) // This line is optional.
 //!                                        // Removing it will result in `None` in the final struct.
 //!         .build();
 //!     println!("Hello, {}!", my_struct.greeting.unwrap_or("World".to_owned()));
-//!     println!("{} is the answer", my_struct.magic[0]);
+//!     println!("{} is the answer", my_struct.magics[0]);
 //! }
 //! ````
 //! This example results in [this code](./fn.example_1_expanded.html) to be generated (after cleaning it up and adding comments).
@@ -97,29 +95,659 @@
 //! }
 //! ````
 //! This example results in [this code](./fn.example_3_expanded.html) to be generated (after cleaning it up and adding comments).
-extern crate syn;
-#[macro_use]
-extern crate quote;
+//!
+//! Checking built value:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_validate(check = "MyStruct::check_range", check = "MyStruct::check_step")]
+//! struct MyStruct {
+//!     start: u32,
+//!     end: u32,
+//!     step: u32,
+//! }
+//!
+//! impl MyStruct {
+//!     fn check_range(&self) -> Result<(), String> {
+//!         if self.start <= self.end {
+//!             Ok(())
+//!         } else {
+//!             Err(format!("{} is after {}", self.start, self.end))
+//!         }
+//!     }
+//!
+//!     fn check_step(&self) -> Result<(), &'static str> {
+//!         if self.step > 0 {
+//!             Ok(())
+//!         } else {
+//!             Err("step is zero")
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let result = Builder::new()
+//!         .start(1)
+//!         .end(10)
+//!         .step(0)
+//!         .build();
+//!     if let Err(error) = result {
+//!         println!("{}", error); // step is zero
+//!     }
+//! }
+//! ````
+//! Checks take the built value by reference and are run together with the validator in the order they are given,
+//! so checks given after the validator see the value returned by it.
+//! Error of the first failing check is converted into the error type given with `error`, or boxed if it isn't given.
+//!
+//! Default values:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct {
+//!     address: String,
+//!     // Uses `Default::default()` when not set
+//!     #[builder_default]
+//!     retries: u32,
+//!     // Uses given expression when not set
+//!     #[builder_default = "30"]
+//!     timeout: u64,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .address("localhost".to_owned())
+//!         .retries(3)
+//!         .build();
+//!     println!("Connecting to {} {} times with timeout {}", my_struct.address, my_struct.retries, my_struct.timeout);
+//! }
+//! ````
+//! Fields with `#[builder_default]` are optional just like `Option` fields.
+//!
+//! Tuple structs:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct(
+//!     #[builder_name = "width"]
+//!     u32,
+//!     #[builder_name = "height"]
+//!     u32,
+//!     Option<String>,
+//! );
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .width(640)
+//!         .height(480)
+//!         .field_2("Screen".to_owned()) // Fields without name are named by their position.
+//!         .build();
+//!     println!("{}x{}", my_struct.0, my_struct.1);
+//! }
+//! ````
+//! `#[builder_name]` can also be used to rename setters of named fields.
+//!
+//! Conversions in setters:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_into]
+//! struct MyStruct {
+//!     name: String,
+//!     nickname: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .name("Bob")
+//!         .nickname("The Builder")
+//!         .build();
+//!     println!("{} {}", my_struct.name, my_struct.nickname.unwrap());
+//! }
+//! ````
+//! With `#[builder_into]` setters take anything that implements `Into` for the type of the field.
+//! It can be given for the whole struct or for single fields.
+//!
+//! Enums:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_names(builder = "AddressBuilder")]
+//! enum Address {
+//!     Tcp {
+//!         host: String,
+//!         port: u16,
+//!     },
+//!     #[builder_names(new = "socket")]
+//!     Unix {
+//!         path: String,
+//!     },
+//! }
+//!
+//! fn main() {
+//!     let address = AddressBuilder::tcp()
+//!         .host("localhost".to_owned())
+//!         .port(8080)
+//!         .build();
+//!     let other = AddressBuilder::socket()
+//!         .path("/tmp/socket".to_owned())
+//!         .build();
+//! }
+//! ````
+//! Every variant gets its own builder (named `AddressBuilderTcp` and `AddressBuilderUnix` here) that is started
+//! from a constructor named after the variant in snake_case (`HTTPServer` becomes `http_server`),
+//! which is raw identifier like `r#match` if the name is a keyword.
+//! Names, prefixes and documentation can be given per variant.
+//!
+//! Collections:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! use std::collections::HashMap;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct {
+//!     #[builder_each = "magic"]
+//!     magics: Vec<i32>,
+//!     #[builder_each = "name"]
+//!     names: HashMap<i32, String>,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .magic(42)
+//!         .magic(7)
+//!         .extend_magics(vec![3, 1])
+//!         .name((42, "answer".to_owned()))
+//!         .build();
+//!     println!("{} is the {}", my_struct.magics[0], my_struct.names[&42]);
+//! }
+//! ````
+//! `#[builder_each]` generates setter that appends single item to collection and `extend_` prefixed setter that appends many.
+//! They work with any collection that implements `Default` and `Extend`.
+//! Such fields are optional and empty by default, unless other default is given with `#[builder_default]`.
+//! Setter for the whole collection is still generated unless it has the same name as the setter for items.
+//!
+//! Checking required fields at runtime:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_mode = "runtime"]
+//! struct MyStruct {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! fn main() {
+//!     let mut builder = Builder::new();
+//!     builder.host("localhost".to_owned());
+//!     if let Err(missing) = builder.build() {
+//!         println!("{}", missing);
+//!     }
+//!     builder.port(8080);
+//!     let my_struct = builder.build().unwrap();
+//!     println!("{}:{}", my_struct.host, my_struct.port);
+//! }
+//! ````
+//! By default type parameters of builder keep track of which required fields are set.
+//! With `#[builder_mode = "runtime"]` builder doesn't have any additional type parameters,
+//! so it can be stored and passed around more easily, and setters take it by mutable reference.
+//! Instead building checks that all required fields are set and returns `MissingFields` error listing them if they aren't.
+//! The error is re-exported next to the builder with the name of the builder as prefix (`BuilderMissingFields` here).
+//! If validator with error type is given, the error type has to implement `From<MissingFields>`.
+//! Values are cloned when building, so builder can be reused.
+//! Builder can also be consumed with `into_build` (or `into_` prefixed name of the build method),
+//! which moves the values out of it, so fields don't need to implement `Clone`.
+//!
+//! Setters that don't change the type of builder can also take it by mutable reference in the default mode:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_setter_style = "mut_ref"]
+//! struct MyStruct {
+//!     host: String,
+//!     port: Option<u16>,
+//! }
+//!
+//! fn main() {
+//!     let mut builder = Builder::new().host("localhost".to_owned());
+//!     if cfg!(debug_assertions) {
+//!         builder.port(8080);
+//!     }
+//!     let my_struct = builder.build();
+//!     println!("{}:{:?}", my_struct.host, my_struct.port);
+//! }
+//! ````
+//! Setters of required fields still take builder by value, because they change its type.
+//! Builder is built from a reference and values are cloned like in runtime mode, or consumed with `into_build`.
+//! Style of setters can be set with `#[builder_setter_style]` to either `owned` or `mut_ref`.
+//! It defaults to `owned` in the default mode and to `mut_ref` in runtime mode.
+//!
+//! Overwriting fields:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_derive(Clone)]
+//! struct MyStruct {
+//!     #[builder_overwrite]
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! fn main() {
+//!     let template = Builder::new()
+//!         .host("localhost".to_owned())
+//!         .port(8080);
+//!     let my_struct = template.clone()
+//!         .host("example.com".to_owned())
+//!         .build();
+//!     println!("{}:{}", my_struct.host, my_struct.port);
+//! }
+//! ````
+//! Setters of required fields can be called only once, unless the field has `#[builder_overwrite]`.
+//! It can also be given for the whole struct, which allows using builders as templates that are cloned and tweaked.
+//!
+//! Optional `Option` fields:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct {
+//!     greeting: Option<String>,
+//!     #[builder_option_names(opt = "maybe_name", clear = "anonymous")]
+//!     name: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let name = std::env::var("NAME").ok();
+//!     let my_struct = Builder::new()
+//!         .greeting("Hello".to_owned())
+//!         .clear_greeting()
+//!         .maybe_name(name)
+//!         .build();
+//!     println!("{}, {}!", my_struct.greeting.unwrap_or("Hi".to_owned()), my_struct.name.unwrap_or("World".to_owned()));
+//! }
+//! ````
+//! Setter of optional `Option` field takes the inner value.
+//! In addition there are setters `{field}_opt` that takes the `Option` as is and `clear_{field}` that sets it to `None`.
+//! If setters have prefix, it's included in both names, like `with_{field}_opt` and `clear_with_{field}`.
+//! Their names can be changed with `#[builder_option_names]`.
+//!
+//! Required `Option` fields:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct {
+//!     #[builder_required]
+//!     parent: Option<u32>,
+//!     #[builder_optional]
+//!     #[builder_default = "8080"]
+//!     port: u16,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .parent(None) // This line is required even though the field is `Option`.
+//!         .build();
+//!     println!("{:?}:{}", my_struct.parent, my_struct.port);
+//! }
+//! ````
+//! Field with `#[builder_required]` is required even if it's `Option` and its setter takes the `Option`.
+//! It can also be given for the whole struct, which makes every `Option` field without default value required.
+//! Fields can be excluded from that with `#[builder_optional]`, which needs a default value for fields that aren't `Option`.
+//!
+//! Aliases of `Option`:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! type Maybe<T> = Option<T>;
+//!
+//! #[derive(Builder)]
+//! #[builder_option_aliases(Maybe)]
+//! struct MyStruct {
+//!     name: std::option::Option<String>,
+//!     nickname: Maybe<String>,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .nickname("Bob".to_owned())
+//!         .build();
+//!     println!("{:?} {:?}", my_struct.name, my_struct.nickname);
+//! }
+//! ````
+//! Fields are recognized as `Option` if their type is `Option`, `std::option::Option` or `core::option::Option`.
+//! Aliases of it aren't known to the derive, so they have to be listed in `#[builder_option_aliases]`.
+//!
+//! Validating fields in setters:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct MyStruct {
+//!     #[builder_validate_field = "not_empty"]
+//!     name: String,
+//!     #[builder_validate_field(validator = "valid_port", error = "PortError", try_prefix)]
+//!     port: Option<u16>,
+//! }
+//!
+//! #[derive(Debug)]
+//! struct PortError;
+//!
+//! fn not_empty(name: String) -> Result<String, &'static str> {
+//!     if name.is_empty() {
+//!         Err("name is empty")
+//!     } else {
+//!         Ok(name)
+//!     }
+//! }
+//!
+//! fn valid_port(port: u16) -> Result<u16, PortError> {
+//!     if port == 0 {
+//!         Err(PortError)
+//!     } else {
+//!         Ok(port)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let error = Builder::new().name("".to_owned()).err().unwrap();
+//!     println!("{}", error); // invalid value for field `name`: name is empty
+//!     let my_struct = Builder::new()
+//!         .name("Bob".to_owned()).unwrap()
+//!         .try_port(8080).unwrap()
+//!         .build();
+//!     println!("{}:{:?}", my_struct.name, my_struct.port);
+//! }
+//! ````
+//! Setters of fields with `#[builder_validate_field]` pass the value through the validator and return `Result`.
+//! Validator takes the value and returns it or an error, which is wrapped into `FieldError` that tells which field was invalid.
+//! Type of the error can be given with `error` key, otherwise errors are converted into `Box<dyn Error + Send + Sync>`.
+//! With `try_prefix` setters are prefixed with `try_`.
+//!
+//! Generated error type:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder, Debug)]
+//! #[builder_mode = "runtime"]
+//! #[builder_error]
+//! #[builder_validate(validator = "MyStruct::validate", error = "String")]
+//! struct MyStruct {
+//!     #[builder_validate_field = "not_empty"]
+//!     name: String,
+//!     balance: i64,
+//! }
+//!
+//! impl MyStruct {
+//!     fn validate(self) -> Result<Self, String> {
+//!         if self.balance < 0 {
+//!             Err(format!("{} has negative balance", self.name))
+//!         } else {
+//!             Ok(self)
+//!         }
+//!     }
+//! }
+//!
+//! fn not_empty(name: String) -> Result<String, &'static str> {
+//!     if name.is_empty() {
+//!         Err("name is empty")
+//!     } else {
+//!         Ok(name)
+//!     }
+//! }
+//!
+//! fn open(name: &str, balance: i64) -> Result<MyStruct, BuilderError> {
+//!     let mut builder = Builder::new();
+//!     builder.name(name.to_owned())?.balance(balance);
+//!     builder.build()
+//! }
+//!
+//! fn main() {
+//!     match open("Bob", -1) {
+//!         Ok(my_struct) => println!("{:?}", my_struct),
+//!         Err(BuilderError::Validation(error)) => println!("{}", error),
+//!         Err(error) => println!("{}", error),
+//!     }
+//! }
+//! ````
+//! With `#[builder_error]` an error type named `{Builder}Error` is generated, or the given name with `#[builder_error = "Name"]`.
+//! It has variants `Field`, `MissingFields` and `Validation` for the errors that can happen with the builder,
+//! and `From` conversions from each of them so that they can be combined with `?`.
+//! It implements `Error` with `Field` and `MissingFields` errors as its source, while error of validator isn't required to implement `Error`.
+//! Builder that can't fail to build or set fields can't have the error.
+//!
+//! Borrowed fields:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! struct Request<'a, 'b: 'a> {
+//!     path: &'a str,
+//!     query: Option<&'b str>,
+//! }
+//!
+//! fn main() {
+//!     let path = "/index.html".to_owned();
+//!     let request = Builder::new()
+//!         .path(&path)
+//!         .query("page=1")
+//!         .build();
+//!     println!("{}?{}", request.path, request.query.unwrap());
+//! }
+//! ````
+//! Lifetimes of the struct, including their bounds, are kept by the builder and are placed before its other parameters.
+//!
+//! Traits for builder:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! use std::collections::HashSet;
+//!
+//! #[derive(Builder, Debug)]
+//! #[builder_derive(Clone, Debug, PartialEq, Eq, Hash, Default, PartialOrd)]
+//! struct MyStruct {
+//!     name: String,
+//!     nickname: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let builder = Builder::default().name("Bob".to_owned());
+//!     let mut builders = HashSet::new();
+//!     builders.insert(builder.clone());
+//!     builders.insert(builder.clone().nickname("The Builder".to_owned()));
+//!     assert!(builders.contains(&builder));
+//!     println!("{:?}", builder.build());
+//! }
+//! ````
+//! Traits given with `#[builder_derive]` are implemented for builder.
+//! `Clone`, `Debug`, `PartialEq`, `Eq` and `Hash` are implemented so that they don't require anything from the states of the fields,
+//! and `Default` is implemented for builder that doesn't have any fields set.
+//! Traits can also be given by their paths like `std::fmt::Debug`.
+//! Other traits are derived for the builder and for the types used as the states of fields,
+//! except for the traits that construct values, so that builder can't have required fields set without values.
+//! Implemented traits are required only from the types of the fields that use generic parameters.
+//! Different bounds can be given with `Trait(bound = "T: Trait")`.
+//!
+//! Debug output of builder:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_derive(Debug)]
+//! struct MyStruct {
+//!     user: String,
+//!     #[builder_debug(redact)]
+//!     password: String,
+//!     #[builder_debug(skip)]
+//!     session: u64,
+//!     #[builder_default = "3"]
+//!     retries: u32,
+//! }
+//!
+//! fn main() {
+//!     let builder = Builder::new().password("hunter2".to_owned());
+//!     assert_eq!("Builder { user: <unset>, password: <redacted>, retries: <default>, .. }", format!("{:?}", builder));
+//! }
+//! ````
+//! Builder shows the fields with their names and whether they are set.
+//! Required fields that aren't set are shown as `<unset>` and optional ones as `<default>`.
+//! Values of fields with `#[builder_debug(redact)]` are replaced by `<redacted>` and fields with `#[builder_debug(skip)]` are left out,
+//! so their types don't need to implement `Debug`.
+//!
+//! Serializing and deserializing builder:
+//!
+//! ````ignore
+//! #[derive(Builder)]
+//! #[builder_mode = "runtime"]
+//! #[builder_derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     host: String,
+//!     port: u16,
+//!     #[builder_default = "30"]
+//!     timeout: u64,
+//! }
+//!
+//! fn main() {
+//!     let mut builder: Builder = serde_json::from_str(r#"{"host": "localhost"}"#).unwrap();
+//!     builder.port(8080);
+//!     assert_eq!(r#"{"host":"localhost","port":8080}"#, serde_json::to_string(&builder).unwrap());
+//!     let my_struct = builder.build().unwrap();
+//! }
+//! ````
+//! With `serde` feature enabled, `Serialize` and `Deserialize` given with `#[builder_derive]` are implemented for builder,
+//! which requires the crate to depend on `serde`.
+//! Builder is serialized as a struct that contains only the fields that are set.
+//! Fields that are missing from the deserialized input are left unset, so they can be set before building,
+//! while unknown and duplicate fields are errors.
+//! Values are validated with the validators of the fields, whose errors need to implement `Display`.
+//! `Deserialize` can only be implemented for builder in runtime mode, as the states of the fields aren't known beforehand.
+//!
+//! Converting back into builder:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder, Clone)]
+//! #[builder_to_builder]
+//! #[builder_overwrite]
+//! struct MyStruct {
+//!     host: String,
+//!     #[builder_default = "80"]
+//!     port: u16,
+//! }
+//!
+//! fn main() {
+//!     let original = Builder::new()
+//!         .host("localhost".to_owned())
+//!         .build();
+//!     let copy = original.to_builder()
+//!         .port(8080)
+//!         .build();
+//!     assert_eq!(("localhost", 80), (&original.host[..], original.port));
+//!     assert_eq!(("localhost", 8080), (&copy.host[..], copy.port));
+//!     let moved = copy.into_builder().host("example.com".to_owned()).build();
+//!     assert_eq!(8080, moved.port);
+//! }
+//! ````
+//! With `#[builder_to_builder]`, struct gets `into_builder` method that converts it into builder that has all fields set,
+//! and `to_builder` method that does the same for its clone and can be used if the struct implements `Clone`.
+//! The consuming method is named `into_builder` rather than `to_builder`, following the convention that `into_` methods take `self`
+//! and `to_` methods take `&self`, so `value.to_builder()` clones the value and requires `Clone`.
+//! The methods are generated only with the attribute, so that they don't conflict with methods of existing structs.
+//! Setting required fields again requires `#[builder_overwrite]` when the builder tracks the fields with its type.
+//! Struct that implements `Drop` can't be converted, as its fields can't be moved out of it.
+//!
+//! Documenting builder:
+//!
+//! ````
+//! #[macro_use]
+//! extern crate bob;
+//!
+//! #[derive(Builder)]
+//! #[builder_docs(builder = "Builds greetings.", new = "Starts a greeting.", append)]
+//! struct MyStruct {
+//!     /// Who is greeted.
+//!     name: String,
+//!     #[builder_docs(setter = "Sets the greeting. Defaults to `Hello`.")]
+//!     greeting: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let my_struct = Builder::new()
+//!         .name("World".to_owned())
+//!         .build();
+//!     println!("{}, {}!", my_struct.greeting.unwrap_or("Hello".to_owned()), my_struct.name);
+//! }
+//! ````
+//! The strings given with `#[builder_docs]` replace the generated documentation of the builder (`builder`),
+//! its constructor (`new`) and its build method (`build`).
+//! If `append` is given, they are appended to the generated documentation instead.
+//! Setters get the doc comments of their fields in addition to the generated documentation
+//! unless it's replaced with `#[builder_docs(setter = "...")]` on the field.
 extern crate proc_macro;
 
+mod attrs;
+mod builder;
+mod generics;
+
 use proc_macro::TokenStream;
-use syn::{Ident, Field, Ty, Lit, Generics, PolyTraitRef, TraitBoundModifier, TyParam, TyParamBound, Body, StrStyle, Attribute, Path, PathSegment, PathParameters, Visibility, MetaItem, NestedMetaItem, AngleBracketedParameterData, parse_path};
+use syn::{parse_macro_input, DeriveInput};
 
-use std::mem::swap;
 use std::fmt::Display;
-use std::collections::HashSet;
-
-use Globalization::*;
 
 /// ````
 /// # use std::fmt::{Debug, Error, Formatter};
-/// # use std::mem::{uninitialized, forget, replace};
-/// # use std::ptr::{write, read};
 /// # use std::marker::PhantomData;
-/// # use _mybuilder::*;
+/// # use _builder::*;
+/// # struct MyStruct { greeting: Option<String>, magics: Vec<i32> }
 /// #[doc(hidden)]
 /// #[allow(unused)]
-/// mod _mybuilder {
+/// mod _builder {
 ///     // Indicates that value isn't set
 ///     pub struct O;
 ///     // Indicates that value is set
@@ -128,6 +756,18 @@ use Globalization::*;
 ///     pub fn id<T>(t: T) -> T {
 ///         t
 ///     }
+///     // Placeholders are shown in debug output of builder in place of values that aren't shown.
+///     # use std::fmt::{Debug, Error, Formatter};
+///     pub struct Placeholder(&'static str);
+///     impl Debug for Placeholder {
+///         fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+///             fmt.write_str(self.0)
+///         }
+///     }
+///     pub const UNSET: Placeholder = Placeholder("<unset>");
+///     pub const DEFAULT: Placeholder = Placeholder("<default>");
+///     pub const REDACTED: Placeholder = Placeholder("<redacted>");
+///     pub const NONE: Placeholder = Placeholder("None");
 /// }
 /// /// Builder for `MyStruct`.
 /// /// # Required fields
@@ -137,7 +777,7 @@ use Globalization::*;
 /// /// * `greeting`
 /// ///
 /// struct Builder<_0> {
-///     _marker: PhantomData<(_0)>,
+///     _marker: PhantomData<(fn() -> MyStruct, _0)>,
 ///     _f1: Option<Vec<i32>>,
 ///     _f0: Option<String>,
 /// }
@@ -157,18 +797,20 @@ use Globalization::*;
 ///     fn clone(&self) -> Self {
 ///         Builder {
 ///             _marker: PhantomData,
-///             _f1: self._f0.clone(),
+///             _f1: self._f1.clone(),
 ///             _f0: self._f0.clone(),
 ///         }
 ///     }
 /// }
 /// impl <_0> Debug for Builder<_0> {
 ///     fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
-///         if let &BuilderInner::Inner { ref _f1, .. } = &self.inner {
-///             fmt.debug_struct(stringify!(Builder))
-///                 .field(stringify!(_f1), &self._f1)
-///                 .field(stringify!(_f0), &self._f0)
-///                 .finish()
+///         fmt.debug_struct("Builder")
+///             .field("greeting", &self._f0)
+///             .field("magics", match self._f1 {
+///                 Some(ref value) => value,
+///                 None => &UNSET,
+///             })
+///             .finish()
 ///     }
 /// }
 /// impl Builder<I> {
@@ -177,8 +819,8 @@ use Globalization::*;
 ///     /// This method is usable only if all required fields are set.
 ///     fn build(self) -> MyStruct {
 ///         id(MyStruct {
-///              magic: self._f1.expect("Type parameters should ensure that there is always value."),
-///              greeting: self._f0,
+///             magics: self._f1.expect("Type parameters should ensure that there is always value."),
+///             greeting: self._f0,
 ///         })
 ///     }
 /// }
@@ -189,6 +831,19 @@ use Globalization::*;
 ///         self
 ///     }
 /// }
+/// impl <_0> Builder<_0> {
+///     /// Setter method for **optional** field `greeting` that takes `Option`.
+///     fn greeting_opt(mut self, greeting: Option<String>) -> Builder<_0> {
+///         self._f0 = greeting;
+///         self
+///     }
+///
+///     /// Sets the field `greeting` to `None`.
+///     fn clear_greeting(mut self) -> Builder<_0> {
+///         self._f0 = None;
+///         self
+///     }
+/// }
 /// impl Builder<O> {
 ///     /// Setter method for **required** field `magics`.
 ///     fn magics(self, magics: Vec<i32>) -> Builder<I> {
@@ -199,17 +854,15 @@ use Globalization::*;
 ///         }
 ///     }
 /// }
+/// # fn main() {}
 /// ````
 #[proc_macro_derive(Dummy1)]
 pub fn example_1_expanded(_: TokenStream) -> TokenStream {unreachable!("Because there cannot be non-procmacro items in procmacro crate this hack is needed.")}
-trait Dummy1 {}
 
 /// ````
-/// # use std::fmt::{Debug, Error, Formatter};
-/// # use std::mem::{uninitialized, forget, replace};
-/// # use std::ptr::{write, read};
 /// # use std::marker::PhantomData;
 /// # use _mybuilder::*;
+/// # struct MyStruct<A, B> { firsts: Vec<A>, seconds: Vec<B> }
 /// #[doc(hidden)]
 /// #[allow(unused)]
 /// mod _mybuilder {
@@ -229,9 +882,9 @@ trait Dummy1 {}
 /// ///
 /// ///
 /// struct MyBuilder<_0, _1, A, B> {
-///     _marker: PhantomData<(_0, _1)>,
-///     _f0: Vec<A>,
-///     _f1: Vec<B>,
+///     _marker: PhantomData<(fn() -> MyStruct<A, B>, _0, _1)>,
+///     _f0: Option<Vec<A>>,
+///     _f1: Option<Vec<B>>,
 /// }
 /// impl <A, B> MyBuilder<O, O, A, B> {
 ///     /// Constructor for builder.
@@ -259,7 +912,7 @@ trait Dummy1 {}
 /// impl <_1, A, B> MyBuilder<O, _1, A, B> {
 ///     /// Setter method for **required** field `firsts`.
 ///     fn set_firsts(self, firsts: Vec<A>) -> MyBuilder<I, _1, A, B> {
-///         Builder {
+///         MyBuilder {
 ///             _marker: PhantomData,
 ///             _f0: Some(firsts),
 ///             _f1: self._f1,
@@ -269,25 +922,24 @@ trait Dummy1 {}
 /// impl <_0, A, B> MyBuilder<_0, O, A, B> {
 ///     /// Setter method for **required** field `seconds`.
 ///     fn with_seconds(self, seconds: Vec<B>) -> MyBuilder<_0, I, A, B> {
-///         Builder {
+///         MyBuilder {
 ///             _marker: PhantomData,
 ///             _f1: Some(seconds),
 ///             _f0: self._f0,
 ///         }
 ///     }
 /// }
+/// # fn main() {}
 /// ````
 #[proc_macro_derive(Dummy2)]
 pub fn example_2_expanded(_: TokenStream) -> TokenStream {unreachable!("Because there cannot be non-procmacro items in procmacro crate this hack is needed.")}
-trait Dummy2 {}
 
 /// ````
-/// # use std::fmt::{Debug, Error, Formatter};
-/// # use std::mem::{uninitialized, forget, replace};
-/// # use std::ptr::{write, read};
 /// # use std::marker::PhantomData;
-/// # use _mybuilder::*;
-///
+/// # use _builder::*;
+/// # struct MyStruct { super_secret: String }
+/// # enum BuildError { CatastrophicFailure }
+/// # impl MyStruct { fn validate(self) -> Result<Self, BuildError> { Ok(self) } }
 /// #[doc(hidden)]
 /// #[allow(unused)]
 /// mod _builder {
@@ -306,7 +958,7 @@ trait Dummy2 {}
 /// ///
 /// ///
 /// struct Builder<_0> {
-///     _marker: PhantomData<(_0)>,
+///     _marker: PhantomData<(fn() -> MyStruct, _0)>,
 ///     _f0: Option<String>,
 /// }
 /// impl Builder<O> {
@@ -339,307 +991,18 @@ trait Dummy2 {}
 ///         }
 ///     }
 /// }
+/// # fn main() {}
 /// ````
 #[proc_macro_derive(Dummy3)]
 pub fn example_3_expanded(_: TokenStream) -> TokenStream {unreachable!("Because there cannot be non-procmacro items in procmacro crate this hack is needed.")}
-trait Dummy3 {}
 
 /// Creates builder for struct annotated with 'Builder' attribute.
-#[proc_macro_derive(Builder, attributes(builder_names, builder_prefix, builder_validate, builder_docs, builder_derive))]
+///
+/// For enums builder is created for each variant and constructors for them are placed in one type.
+#[proc_macro_derive(Builder, attributes(builder_names, builder_prefix, builder_validate, builder_docs, builder_derive, builder_default, builder_name, builder_into, builder_each, builder_mode, builder_setter_style, builder_overwrite, builder_option_names, builder_required, builder_optional, builder_option_aliases, builder_validate_field, builder_error, builder_debug, builder_to_builder))]
 pub fn create_builder(input: TokenStream) -> TokenStream {
-    let item = syn::parse_derive_input(&input.to_string()).unwrap();
-    if let Body::Struct(s) = item.body {
-        let (builder, new, build) = get_builder_names(&item.attrs);
-        let prefix = get_setter_prefix(&item.attrs, Ident::new(""));
-        let derives = get_derives(&item.attrs);
-        // This module holds types generated so they don't conflict with user added/generated by other invocations of this.
-        let builder_mod = Ident::new(format!("_{}", builder.to_string().to_lowercase()));
-        let (validator, validator_error) = get_validator(&item.attrs, format!("{}::id", builder_mod));
-
-        let name = &item.ident;
-        let vis = &item.vis;
-        let (impl_generics, ty_generics, _) = item.generics.split_for_impl();
-
-        // Fields need to be renamed so that they don't conficlict with _marker field.
-        let (opt_fields, fields): (Vec<_>, Vec<_>)
-            = s.fields()
-                .iter()
-                .enumerate()
-                .map(|(i, f)| (Ident::new(format!("_f{}", i)), f))
-                .partition(|&(_, ref f)| is_option(&f.ty));
-
-        // Required fields are wrapped into Option so that they can have initial value.
-        // TODO: When union becomes stable this can be implemented with uninitialized memory.
-        let builder_fields = &fields.iter()
-            .map(|&(ref i, ref f)| priv_field(i.clone(), wrap_into_option(f.ty.clone())))
-            .collect::<Vec<_>>();
-        let builder_field_names = &builder_fields.iter()
-            .map(|f| f.ident.clone())
-            .collect::<Vec<_>>();
-        let builder_field_names2 = builder_field_names;
-        // Optional values already have initial value.
-        let builder_opt_fields = &opt_fields.iter()
-            .map(|&(ref i, ref f)| priv_field(i.clone(), f.ty.clone()))
-            .collect::<Vec<_>>();
-        let builder_opt_field_names = &builder_opt_fields.iter()
-            .map(|f| f.ident.clone())
-            .collect::<Vec<_>>();
-        let builder_opt_field_names2 = builder_opt_field_names;
-        let result_fields = fields.iter().map(|&(_, f)| &f.ident);
-        let result_opt_fields = opt_fields.iter().map(|&(_, f)| &f.ident);
-
-        let builder_plain_ty_params = &(0..builder_fields.len())
-            .map(|i| plain_ty_param(format!("_{}", i)))
-            .collect::<Vec<_>>();
-        // Type parameters for builders required fields
-        let builder_ty_params = &(0..builder_fields.len())
-            .map(|i| plain_ty_param(format!("_{}", i)))
-            .collect::<Vec<_>>();
-
-        // All type parameters that the builder has.
-        let mut ext_generics = item.generics.clone();
-        add_ty_params(&mut ext_generics, builder_ty_params.clone());
-        let (ext_impl_generics, ext_ty_generics, ext_where_clause) = ext_generics.split_for_impl();
-
-        // All type parameters that the builder has with additional Clone bound.
-        let mut ext_clone_generics = item.generics.clone();
-        ext_clone_generics.ty_params = ext_clone_generics.ty_params
-            .into_iter()
-            .map(|mut ty| {
-                ty.bounds.push(ty_param_bound(vec!["Clone".into()], Local));
-                ty
-            })
-            .collect::<Vec<_>>();
-        add_ty_params(&mut ext_clone_generics, builder_ty_params.clone());
-        let (ext_clone_impl_generics, _, _) = ext_clone_generics.split_for_impl();
-
-        // All type parameters that the builder has with additional Debug bound.
-        let mut ext_debug_generics = item.generics.clone();
-        ext_debug_generics.ty_params = ext_debug_generics.ty_params
-            .into_iter()
-            .map(|mut ty| {
-                ty.bounds.push(ty_param_bound(vec!["std".into(), "fmt".into(), "Debug".into()], Global));
-                ty
-            })
-            .collect::<Vec<_>>();
-        add_ty_params(&mut ext_debug_generics, builder_ty_params.clone());
-        let (ext_debug_impl_generics, _, _) = ext_debug_generics.split_for_impl();
-
-        // Type parameters for constructor.
-        // At the start builder doesn't have any values set.
-        let mut start_generics = item.generics.clone();
-        add_ty_params(&mut start_generics,
-            (0..builder_fields.len())
-                .map(|_| plain_ty_param(format!("{}::O", builder_mod))));
-        let (_, start_ty_generics, start_where_clause) = start_generics.split_for_impl();
-
-        // Type parameters for build method.
-        // When building we require that every required value is set.
-        let mut end_generics = item.generics.clone();
-        add_ty_params(&mut end_generics,
-            (0..builder_fields.len())
-                .map(|_| plain_ty_param(format!("{}::I", builder_mod))));
-        let (_, end_ty_generics, _) = end_generics.split_for_impl();
-
-        let required = if fields.is_empty() {
-            "".into()
-        } else {
-            fields.iter()
-                .cloned()
-                .map(|(i, f)| f.ident.clone().unwrap_or((&i.as_ref()[1..]).into()))
-                .map(|i| format!("* `{}`\n", i))
-                .fold("# Required fields\n".to_owned(), |a, b| a + &b)
-        };
-        let optional = if opt_fields.is_empty() {
-            "".into()
-        } else {
-            opt_fields.iter()
-                .cloned()
-                .map(|(i, f)| f.ident.clone().unwrap_or((&i.as_ref()[1..]).into()))
-                .map(|i| format!("* `{}`\n", i))
-                .fold("# Optional fields\n".to_owned(), |a, b| a + &b)
-        };
-
-        let builder_doc = format!("Builder for `{}`.\n{}\n{}", name, required, optional);
-        let constructor_doc = format!("Constructor for builder.\n\nAll fields are unset at the start.");
-        let build_doc = format!("Builds new `{}`.\n\nThis method is usable only if all required fields are set.", name);
-        let mut tks = quote!(
-            #[doc(hidden)]
-            #[allow(unused)]
-            #vis mod #builder_mod {
-                // Indicates that value isn't set
-                pub struct O;
-                // Indicates that value is set
-                pub struct I;
-                // This function is is used for when user doesn't provide validation function.
-                pub fn id<T>(t: T) -> T {t}
-            }
-
-            #[doc = #builder_doc]
-            #vis struct #builder #ext_impl_generics #ext_where_clause {
-                _marker: ::std::marker::PhantomData<(#(#builder_plain_ty_params),*)>,
-                #(#builder_fields,)*
-                #(#builder_opt_fields),*
-            }
-
-            impl #impl_generics #builder #start_ty_generics #start_where_clause {
-                #[doc = #constructor_doc]
-                #vis fn #new() -> #builder #start_ty_generics {
-                    #builder {
-                        _marker: ::std::marker::PhantomData,
-                        #(#builder_field_names: None,)*
-                        #(#builder_opt_field_names: None),*
-                    }
-                }
-            }
-        );
-
-        if derives.contains("Clone") {
-            let parsed: String = quote!(
-                impl #ext_clone_impl_generics Clone for #builder #ext_ty_generics #ext_where_clause {
-                    fn clone(&self) -> Self {
-                        #builder {
-                            _marker: ::std::marker::PhantomData,
-                            #(#builder_field_names: self.#builder_field_names2.clone(),)*
-                            #(#builder_opt_field_names: self.#builder_opt_field_names2.clone()),*
-                        }
-                    }
-                }
-            ).parse().unwrap();
-            tks.append(&parsed);
-        }
-
-        if derives.contains("Debug") {
-            let parsed: String = quote!(
-                impl #ext_debug_impl_generics ::std::fmt::Debug for #builder #ext_ty_generics #ext_where_clause {
-                    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
-                        fmt.debug_struct(stringify!(#builder))
-                            #(.field(stringify!(#builder_field_names), &self.#builder_field_names2))*
-                            #(.field(stringify!(#builder_opt_field_names), &self.#builder_opt_field_names2))*
-                            .finish()
-                    }
-                }
-            ).parse().unwrap();
-            tks.append(&parsed);
-        }
-
-        let parsed: String = if let Some(error) = validator_error {
-            quote!(
-                impl #impl_generics #builder #end_ty_generics
-                    #ext_where_clause
-                {
-                    #[doc = #build_doc]
-                    #vis fn #build(self) -> Result<#name #ty_generics, #error> {
-                        #validator(#name {
-                            #(#result_fields: self.#builder_field_names.expect("Type parameters should ensure that there is always value."),)*
-                            #(#result_opt_fields: self.#builder_opt_field_names),*
-                        })
-                    }
-                }
-            ).parse().unwrap()
-        } else {
-            quote!(
-                impl #impl_generics #builder #end_ty_generics
-                    #ext_where_clause
-                {
-                    #[doc = #build_doc]
-                    #vis fn #build(self) -> #name #ty_generics {
-                        #validator(#name {
-                            #(#result_fields: self.#builder_field_names.expect("Type parameters should ensure that there is always value."),)*
-                            #(#result_opt_fields: self.#builder_opt_field_names),*
-                        })
-                    }
-                }
-            ).parse().unwrap()
-        };
-        tks.append(&parsed);
-
-        for (i, &(ref fname, ref field)) in opt_fields.iter().enumerate() {
-            // This being optional field doesn't mean that the setter takes optional.
-            let ty = unwrap_from_option(&field.ty).expect("Tried to get inner type from non-Option.");
-
-            // Per field prefixes are supported
-            let prefix = get_setter_prefix(&field.attrs, prefix.clone());
-            let raw_name = field.ident.clone().unwrap_or_else(|| i.to_string().into());
-            let name = Ident::new(&format!("{}{}", prefix, raw_name)[..]);
-
-            let setter_doc = format!("Setter method for **optional** field `{}`.", raw_name);
-            let parsed: String = quote!(
-                impl #ext_impl_generics #builder #ext_ty_generics #ext_where_clause {
-                    #[doc = #setter_doc]
-                    #vis fn #name(mut self, #raw_name: #ty) -> #builder #ext_ty_generics {
-                        self.#fname = Some(#raw_name);
-                        self
-                    }
-                }
-            ).parse().unwrap();
-            tks.append(&parsed);
-        }
-
-        for (i, &(ref fname, ref field)) in fields.iter().enumerate() {
-            let ty = &field.ty;
-
-            // All but the field that the setter is setting needs to be just taken from self.
-            let mut builder_field_names = builder_field_names.clone();
-            builder_field_names.remove(i);
-            let builder_field_names = &builder_field_names;
-            let builder_field_names2 = builder_field_names;
-
-            // Per field prefixes are supported
-            let prefix = get_setter_prefix(&field.attrs, prefix.clone());
-            let raw_name = field.ident.clone().unwrap_or_else(|| i.to_string().into());
-            let name = Ident::new(&format!("{}{}", prefix, raw_name)[..]);
-
-            // Because one concrete type parameter is used, all but one unbound one is needed.
-            let mut other_generics = item.generics.clone();
-            add_ty_params(&mut other_generics, builder_ty_params
-                .iter().enumerate()
-                .filter_map(|(j, t)| if i == j {
-                    None
-                } else {
-                    Some(t.clone())
-                }));
-            let (other_impl_generics, _, _) = other_generics.split_for_impl();
-
-            let change_index = |(j, mut t): (_, TyParam), ident: String| {
-                if i == j { t.ident = ident.into(); }
-                t
-            };
-
-            // Fields can be set only once, so we require that field wasn't set before.
-            let mut set_generics = item.generics.clone();
-            add_ty_params(&mut set_generics, builder_ty_params.clone()
-                .into_iter().enumerate()
-                .map(|n| change_index(n, format!("{}::O", builder_mod))));
-            let (_, set_ty_generics, _) = set_generics.split_for_impl();
-
-            // After setting field, type parameter is changed to indicate that.
-            let mut after_set_generics = item.generics.clone();
-            add_ty_params(&mut after_set_generics, builder_ty_params.clone()
-                .into_iter().enumerate()
-                .map(|n| change_index(n, format!("{}::I", builder_mod))));
-            let (_, after_set_ty_generics, _) = after_set_generics.split_for_impl();
-
-            let setter_doc = format!("Setter method for **required** field `{}`.", raw_name);
-            let parsed: String = quote!(
-                impl #other_impl_generics #builder #set_ty_generics #ext_where_clause {
-                    #[doc = #setter_doc]
-                    #vis fn #name(self, #raw_name: #ty) -> #builder #after_set_ty_generics {
-                        #builder {
-                            _marker: ::std::marker::PhantomData,
-                            #fname: Some(#raw_name),
-                            #(#builder_field_names: self.#builder_field_names2,)*
-                            #(#builder_opt_field_names: self.#builder_opt_field_names2),*
-                        }
-                    }
-                }
-            ).parse().unwrap();
-            tks.append(&parsed);
-        }
-        debug_display(tks.parse().unwrap())
-    } else {
-        panic!("Only structs supported.");
-    }
+    let item = parse_macro_input!(input as DeriveInput);
+    debug_display(builder::create_builder(&item)).into()
 }
 
 #[inline(always)]
@@ -647,224 +1010,3 @@ fn debug_display<T: Display>(t: T) -> T {
     //println!("{}", t);
     t
 }
-
-/// Wraps type T into Option<T>
-fn wrap_into_option(ty: Ty) -> Ty {
-    let mut params = AngleBracketedParameterData::default();
-    params.types.push(ty);
-    Ty::Path(None, PathSegment {
-            ident: Ident::new("Option"),
-            parameters: PathParameters::AngleBracketed(params),
-        }.into())
-}
-
-
-/// Returns inner type T of Option<T> or None if type wasn't Option.
-fn unwrap_from_option(ty: &Ty) -> Option<&Ty> {
-    if let &Ty::Path(_, Path{ref segments, ..}) = ty {
-        let &PathSegment{ref ident, ref parameters} = &segments[0];
-        if ident == "Option" {
-            if let &PathParameters::AngleBracketed(ref a) = parameters {
-                return a.types.get(0)
-            }
-        }
-    }
-    None
-}
-
-/// Checks if give type is Option
-fn is_option(ty: &Ty) -> bool {
-    if let &Ty::Path(_, ref p) = ty {
-        if let Some(s) = p.segments.get(0) {
-            return s.ident == "Option";
-        }
-    }
-    false
-}
-
-/// Collects iterators next element and panics with message if there is still elements left after that.
-fn collect_most_one<I, T>(mut iter: I, message: &'static str) -> Option<T>
-    where I: Iterator<Item=T>
-{
-    let result = iter.next();
-    assert!(iter.fuse().next().is_none(), message);
-    result
-}
-
-enum Named {
-    Builder,
-    New,
-    Build,
-}
-
-impl Named {
-    fn from_str(s: &str) -> Option<Named> {
-        use Named::*;
-        match s {
-            "builder" => Some(Builder),
-            "new" => Some(New),
-            "build" => Some(Build),
-            _ => None,
-        }
-    }
-}
-
-/// Gets builders, builders constructors and build methods names based on attribute and falls back to default ones if no attribute present.
-fn get_builder_names(attrs: &[Attribute]) -> (Ident, Ident, Ident) {
-    let mut iter = attrs.iter()
-        .filter_map(|a| {
-            if let MetaItem::List(ref name, ref value) = a.value {
-                if name == "builder_names" {
-                    return Some(value);
-                }
-            }
-            None
-        });
-    collect_most_one(&mut iter, "Only one #[builder_name] attribute supported for struct.")
-        .unwrap_or(&vec![])
-        .iter()
-        .filter_map(|v| {
-            if let &NestedMetaItem::MetaItem(MetaItem::NameValue(ref name, ref value)) = v {
-                if let Some(which) = Named::from_str(name.as_ref()) {
-                    if let &Lit::Str(ref value, StrStyle::Cooked) = value {
-                        return Some((which, Ident::new(&value[..])));
-                    }
-                }
-            }
-            None
-        })
-        .fold((Ident::new("Builder"), Ident::new("new"), Ident::new("build")), |(builder, new, build), (which, v)| {
-            use Named::*;
-            match which {
-                Builder => (v, new, build),
-                New => (builder, v, build),
-                Build => (builder, new, v),
-            }
-        })
-}
-
-fn get_derives(attrs: &[Attribute]) -> HashSet<String> {
-    attrs.iter()
-        .flat_map(|a| {
-            if let MetaItem::List(ref name, ref value) = a.value {
-                if name == "builder_derive" {
-                    return value.iter()
-                        .filter_map(|v| {
-                            if let &NestedMetaItem::MetaItem(ref meta) = v {
-                                if let &MetaItem::Word(ref ident) = meta {
-                                    return Some(ident.as_ref().to_owned());
-                                }
-                            }
-                            None
-                        })
-                        .collect();
-                }
-            }
-            vec![]
-        })
-        .collect()
-}
-
-/// Gets setter prefix based on attribute and falls back to default given as parameter if no attribute present.
-fn get_setter_prefix(attrs: &[Attribute], default: Ident) -> Ident {
-    let mut iter = attrs.iter()
-        .filter_map(|a| {
-            if let MetaItem::NameValue(ref name, ref value) = a.value {
-                if name == "builder_prefix" {
-                    if let &Lit::Str(ref value, StrStyle::Cooked) = value {
-                        return Some(Ident::new(&value[..]));
-                    }
-                }
-            }
-            None
-        });
-    collect_most_one(&mut iter, "Only one #[builder_prefix] attribute supported per item.")
-        .unwrap_or(default)
-}
-
-/// Gets validator function and error type based on attribute and falls back to default if there isn't one.
-fn get_validator<P: Into<Path>>(attrs: &[Attribute], default: P) -> (Path, Option<Path>) {
-    let mut iter = attrs.iter()
-        .filter_map(|a| {
-            if let MetaItem::List(ref name, ref value) = a.value {
-                if name == "builder_validate" {
-                    return Some(value);
-                }
-            }
-            None
-        });
-    let result = collect_most_one(&mut iter, "Only one #[builder_validate] attribute supported for struct.");
-    if let Some(r) = result {
-        let (v, e) = r.iter()
-        .filter_map(|v| {
-            if let &NestedMetaItem::MetaItem(MetaItem::NameValue(ref name, ref value)) = v {
-                if name == "validator" || name == "error" {
-                    if let &Lit::Str(ref value, StrStyle::Cooked) = value {
-                        return Some((name == "validator", parse_path(&value[..]).expect("Malformed path given to `builder_validate` attribute")));
-                    }
-                }
-            }
-            None
-        })
-        .fold((None, None), |(validator, error), (first, v)| {
-            if first {
-                (Some(v), error)
-            } else {
-                (validator, Some(v))
-            }
-        });
-        (v.expect("Validator function has to be provided for `builder_validate` attribute."), e)
-    } else {
-        (default.into(), None)
-    }
-}
-
-
-/// Constructs type parameter without bounds from identifier.
-fn plain_ty_param<I: Into<Ident>>(ident: I) -> TyParam {
-    TyParam {
-        ident: ident.into(),
-        attrs: vec![],
-        bounds: vec![],
-        default: None,
-    }
-}
-
-/// Constructs private field from identifier and type.
-fn priv_field<I: Into<Ident>>(ident: I, ty: Ty) -> Field {
-    Field {
-        ident: Some(ident.into()),
-        vis: Visibility::Inherited,
-        attrs: vec![],
-        ty: ty,
-    }
-}
-
-/// Adds type parameters to the start of generics.
-fn add_ty_params<I: IntoIterator<Item=TyParam>>(generics: &mut Generics, ty_params: I) {
-    let mut empty = vec![];
-    swap(&mut empty, &mut generics.ty_params);
-    generics.ty_params = ty_params.into_iter()
-        .chain(empty)
-        .collect();
-}
-
-#[derive(PartialEq, Eq)]
-enum Globalization {
-    Global,
-    Local,
-}
-
-/// Creates type parameter bound based on path segments
-fn ty_param_bound(segments: Vec<PathSegment>, global: Globalization) -> TyParamBound {
-    TyParamBound::Trait(
-        PolyTraitRef{
-            bound_lifetimes: vec![],
-            trait_ref: Path{
-                global: global == Global,
-                segments: segments
-            }
-        },
-        TraitBoundModifier::None
-    )
-}
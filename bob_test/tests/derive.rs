@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate bob;
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+
 #[derive(Builder, Debug)]
 #[builder_names(builder = "Builder")]
 #[builder_derive(Clone, Debug)]
@@ -48,6 +50,53 @@ pub struct Struct3<T: Eq> {
     c: i32,
 }
 
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderDocs")]
+#[builder_docs(builder = "This is appended.", append)]
+pub struct StructDocs {
+    /// This is forwarded to the setter.
+    /// Over multiple lines.
+    a: i32,
+    #[builder_docs(setter = "This replaces the setter documentation.")]
+    b: Option<i32>,
+    /// This is kept.
+    #[builder_docs(setter = "This is appended to the setter documentation.", append)]
+    c: i32,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderDefault")]
+pub struct StructDefault {
+    a: i32,
+    #[builder_default]
+    b: Vec<i32>,
+    #[builder_default = "42"]
+    c: u32,
+    #[builder_default = "Some(\"default\".to_owned())"]
+    d: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderTuple")]
+pub struct StructTuple(
+    #[builder_name = "width"]
+    u32,
+    #[builder_prefix = "set_"]
+    u32,
+    Option<String>,
+);
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderUnit")]
+pub struct StructUnit;
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderRenamed")]
+pub struct StructRenamed {
+    #[builder_name = "renamed"]
+    a: i32,
+}
+
 #[derive(Debug)]
 pub struct Unclone<T>(T);
 
@@ -63,6 +112,300 @@ pub struct Struct5 {
     a: Option<Unclone<i32>>,
 }
 
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderInto")]
+pub struct StructInto {
+    #[builder_into]
+    a: String,
+    #[builder_into]
+    b: Option<Vec<u8>>,
+    c: u64,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderIntoAll")]
+#[builder_into]
+pub struct StructIntoAll<T> {
+    a: String,
+    b: Option<T>,
+    #[builder_default]
+    c: u64,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderEach")]
+#[builder_prefix = "set_"]
+pub struct StructEach {
+    #[builder_each = "magic"]
+    magics: Vec<i32>,
+    #[builder_each = "entry"]
+    entries: HashMap<String, u32>,
+    #[builder_each = "tag"]
+    #[builder_default = "vec![\"default\".to_owned()]"]
+    #[builder_into]
+    tags: Vec<String>,
+    #[builder_each = "flag"]
+    flags: Option<BTreeSet<u8>>,
+    #[builder_prefix = ""]
+    #[builder_each = "other"]
+    other: Vec<u8>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderMutRef")]
+#[builder_setter_style = "mut_ref"]
+pub struct StructMutRef {
+    a: String,
+    b: Option<u32>,
+    #[builder_default]
+    #[builder_each = "item"]
+    c: Vec<u32>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderOverwrite")]
+#[builder_derive(Clone)]
+pub struct StructOverwrite {
+    #[builder_overwrite]
+    a: String,
+    b: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderOverwriteAll")]
+#[builder_derive(Clone)]
+#[builder_overwrite]
+pub struct StructOverwriteAll<T> {
+    a: T,
+    b: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderOptional")]
+#[builder_into]
+pub struct StructOptional {
+    a: Option<String>,
+    #[builder_default = "Some(1)"]
+    b: Option<u32>,
+    #[builder_option_names(opt = "maybe_c", clear = "no_c")]
+    #[builder_prefix = "with_"]
+    c: Option<u32>,
+    #[builder_default = "Some(vec![1])"]
+    #[builder_each = "item"]
+    d: Option<Vec<u32>>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderRequired")]
+pub struct StructRequired {
+    #[builder_required]
+    a: Option<u32>,
+    #[builder_optional]
+    #[builder_default = "2"]
+    b: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderRequiredAll")]
+#[builder_required]
+pub struct StructRequiredAll {
+    a: Option<u32>,
+    #[builder_optional]
+    b: Option<u32>,
+    #[builder_default = "Some(3)"]
+    c: Option<u32>,
+}
+
+pub mod custom {
+    /// Type that is named like `Option`, but isn't one.
+    #[derive(Debug, PartialEq)]
+    pub struct Option<T>(pub T);
+}
+
+pub type Maybe<T> = Option<T>;
+pub type MaybeU32 = std::option::Option<u32>;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderOptionPaths")]
+#[builder_option_aliases(Maybe, MaybeU32)]
+pub struct StructOptionPaths<T> {
+    a: std::option::Option<u32>,
+    b: ::core::option::Option<T>,
+    c: custom::Option<u32>,
+    d: Maybe<T>,
+    e: MaybeU32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderValidated")]
+#[builder_into]
+pub struct StructValidated {
+    #[builder_validate_field(validator = "validate_port", error = "PortError")]
+    port: u16,
+    #[builder_validate_field = "validate_name"]
+    name: Option<String>,
+    #[builder_validate_field(validator = "validate_port", error = "PortError", try_prefix)]
+    #[builder_default = "80"]
+    fallback: u16,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PortError(u16);
+
+fn validate_port(port: u16) -> Result<u16, PortError> {
+    if port < 1024 {
+        Err(PortError(port))
+    } else {
+        Ok(port)
+    }
+}
+
+fn validate_name(name: String) -> Result<String, String> {
+    if name.is_empty() {
+        Err("empty name".to_owned())
+    } else {
+        Ok(name)
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderErrors")]
+#[builder_error = "ErrorsError"]
+#[builder_validate(validator = "StructErrors::validate", error = "PortError")]
+pub struct StructErrors {
+    #[builder_validate_field(validator = "validate_port", error = "PortError")]
+    port: u16,
+}
+
+impl StructErrors {
+    fn validate(self) -> Result<Self, PortError> {
+        if self.port == 8080 {
+            Err(PortError(self.port))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl std::fmt::Display for PortError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "port {} is reserved", self.0)
+    }
+}
+
+impl std::error::Error for PortError {}
+
+fn build_errors(port: u16) -> Result<StructErrors, ErrorsError> {
+    BuilderErrors::new().port(port)?.build()
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderChecked")]
+#[builder_validate(check = "StructChecked::check_order", check = "StructChecked::check_len")]
+pub struct StructChecked {
+    from: u32,
+    to: u32,
+}
+
+impl StructChecked {
+    fn check_order(&self) -> Result<(), &'static str> {
+        if self.from <= self.to {
+            Ok(())
+        } else {
+            Err("from is after to")
+        }
+    }
+
+    fn check_len(&self) -> Result<(), String> {
+        if self.to - self.from > 10 {
+            Err(format!("length {} is too long", self.to - self.from))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BuilderTraits")]
+#[builder_derive(Clone, Debug, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct StructTraits<T> {
+    a: T,
+    b: Option<u32>,
+}
+
+/// Type that doesn't implement any traits.
+pub struct Secret(&'static str);
+
+#[derive(Builder)]
+#[builder_names(builder = "BuilderDebug")]
+#[builder_derive(Debug)]
+pub struct StructDebug {
+    user: String,
+    #[builder_debug(redact)]
+    password: Secret,
+    #[builder_debug(skip)]
+    key: Secret,
+    #[builder_default = "3"]
+    retries: u32,
+    #[builder_debug(redact)]
+    token: Option<Secret>,
+    r#type: Option<u32>,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "BuilderQualified")]
+#[builder_derive(std::fmt::Debug, ::core::clone::Clone, core::cmp::PartialEq, std::default::Default)]
+pub struct StructQualified {
+    user: String,
+    #[builder_debug(redact)]
+    password: String,
+}
+
+/// Generated code doesn't depend on the names in scope of the item.
+pub mod shadowed {
+    #[derive(Debug)]
+    pub struct Error;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Builder, Debug)]
+    #[builder_names(builder = "BuilderShadowed")]
+    #[builder_derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+    pub struct StructShadowed {
+        pub a: u32,
+        pub b: Option<u32>,
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder_names(builder = "BuilderCopied")]
+#[builder_to_builder]
+#[builder_overwrite]
+pub struct StructCopied {
+    a: String,
+    b: Option<u32>,
+    #[builder_default = "5"]
+    c: u32,
+    #[builder_default = "Some(5)"]
+    d: Option<u32>,
+}
+
+/// Struct that can only be converted into builder by value, as it doesn't implement Clone.
+#[derive(Builder)]
+#[builder_names(builder = "BuilderMoved")]
+#[builder_to_builder]
+pub struct StructMoved(Secret, #[builder_default] u32);
+
+#[derive(Builder)]
+#[builder_names(builder = "BuilderSyntax")]
+pub struct StructSyntax<F: Fn(u32) -> u32> {
+    r#type: String,
+    #[builder_prefix = "set_"]
+    r#match: Option<u32>,
+    callback: Box<dyn Fn(u32) -> u32>,
+    f: F,
+}
+
 #[test]
 fn build() {
     let builder = Builder::new()
@@ -90,4 +433,331 @@ fn build() {
     assert_eq!(Some("World"), built.a);
     assert_eq!(None, built.b);
     assert_eq!(-42, built.c);
+    let built = Builder4::new()
+        .a(Unclone(4))
+        .build();
+    assert_eq!(4, built.a.0);
+    let built = Builder5::new()
+        .a(Unclone(5))
+        .build();
+    assert_eq!(Some(5), built.a.map(|a| a.0));
+}
+
+#[test]
+fn docs() {
+    let built = BuilderDocs::new()
+        .a(1)
+        .b(2)
+        .c(3)
+        .build();
+    assert_eq!(1, built.a);
+    assert_eq!(Some(2), built.b);
+    assert_eq!(3, built.c);
+}
+
+#[test]
+fn default() {
+    let built = BuilderDefault::new()
+        .a(1)
+        .build();
+    assert_eq!(1, built.a);
+    assert_eq!(Vec::<i32>::new(), built.b);
+    assert_eq!(42, built.c);
+    assert_eq!(Some("default".to_owned()), built.d);
+    let built = BuilderDefault::new()
+        .b(vec![2])
+        .c(3)
+        .d("set".to_owned())
+        .a(4)
+        .build();
+    assert_eq!(4, built.a);
+    assert_eq!(vec![2], built.b);
+    assert_eq!(3, built.c);
+    assert_eq!(Some("set".to_owned()), built.d);
+}
+
+#[test]
+fn tuple() {
+    let built = BuilderTuple::new()
+        .field_2("label".to_owned())
+        .set_field_1(2)
+        .width(1)
+        .build();
+    assert_eq!(1, built.0);
+    assert_eq!(2, built.1);
+    assert_eq!(Some("label".to_owned()), built.2);
+    let StructUnit = BuilderUnit::new().build();
+    let built = BuilderRenamed::new()
+        .renamed(3)
+        .build();
+    assert_eq!(3, built.a);
+}
+
+#[test]
+fn syntax() {
+    let built = BuilderSyntax::new()
+        .f(|a| a + 1)
+        .callback(Box::new(|a| a * 2))
+        .r#type("raw".to_owned())
+        .set_match(3)
+        .build();
+    assert_eq!("raw", built.r#type);
+    assert_eq!(Some(3), built.r#match);
+    assert_eq!(7, (built.f)((built.callback)(built.r#match.unwrap())));
+}
+
+#[test]
+fn into() {
+    let built = BuilderInto::new()
+        .c(1)
+        .b("bytes")
+        .a("string")
+        .build();
+    assert_eq!("string", built.a);
+    assert_eq!(Some(b"bytes".to_vec()), built.b);
+    assert_eq!(1, built.c);
+    let built = BuilderIntoAll::new()
+        .a('a')
+        .b(1u8)
+        .c(2u32)
+        .build();
+    assert_eq!("a", built.a);
+    assert_eq!(Some(1u16), built.b);
+    assert_eq!(2, built.c);
+}
+
+#[test]
+fn each() {
+    let built = BuilderEach::new().build();
+    assert!(built.magics.is_empty());
+    assert!(built.entries.is_empty());
+    assert_eq!(vec!["default".to_owned()], built.tags);
+    assert_eq!(None, built.flags);
+    let built = BuilderEach::new()
+        .magic(42)
+        .magic(7)
+        .extend_magics(vec![3, 1])
+        .entry(("a".to_owned(), 1))
+        .extend_entries(vec![("b".to_owned(), 2)])
+        .tag("tag")
+        .flag(2)
+        .flag(1)
+        .other(1)
+        .build();
+    assert_eq!(vec![42, 7, 3, 1], built.magics);
+    assert_eq!(Some(&2), built.entries.get("b"));
+    assert_eq!(2, built.entries.len());
+    assert_eq!(vec!["default".to_owned(), "tag".to_owned()], built.tags);
+    assert_eq!(Some(vec![1, 2]), built.flags.map(|f| f.into_iter().collect::<Vec<_>>()));
+    assert_eq!(vec![1], built.other);
+    let built = BuilderEach::new()
+        .set_magics(vec![1])
+        .magic(2)
+        .set_tags(vec![])
+        .tag("tag")
+        .build();
+    assert_eq!(vec![1, 2], built.magics);
+    assert_eq!(vec!["tag".to_owned()], built.tags);
+}
+
+#[test]
+fn mut_ref() {
+    let mut builder = BuilderMutRef::new().a("a".to_owned());
+    for i in 0..3 {
+        if i % 2 == 0 {
+            builder.item(i);
+        }
+    }
+    let built = builder.build();
+    assert_eq!(StructMutRef { a: "a".to_owned(), b: None, c: vec![0, 2] }, built);
+    builder.b(1).item(4);
+    assert_eq!(StructMutRef { a: "a".to_owned(), b: Some(1), c: vec![0, 2, 4] }, builder.build());
+}
+
+#[test]
+fn overwrite() {
+    let template = BuilderOverwrite::new().a("template".to_owned());
+    let built = template.clone()
+        .a("changed".to_owned())
+        .b(1)
+        .a("changed again".to_owned())
+        .build();
+    assert_eq!(StructOverwrite { a: "changed again".to_owned(), b: 1 }, built);
+    assert_eq!(StructOverwrite { a: "template".to_owned(), b: 2 }, template.b(2).build());
+
+    let template = BuilderOverwriteAll::new().a(1).b(2);
+    assert_eq!(StructOverwriteAll { a: 1, b: 3 }, template.clone().b(3).build());
+    assert_eq!(StructOverwriteAll { a: 4, b: 2 }, template.a(4).build());
+}
+
+#[test]
+fn optional() {
+    let built = BuilderOptional::new()
+        .a("a")
+        .with_c(3u8)
+        .item(2u8)
+        .build();
+    assert_eq!(StructOptional { a: Some("a".to_owned()), b: Some(1), c: Some(3), d: Some(vec![1, 2]) }, built);
+
+    let built = BuilderOptional::new()
+        .a_opt(None)
+        .b(2u8)
+        .clear_b()
+        .maybe_c(Some(3))
+        .d_opt(Some(vec![]))
+        .item(1u8)
+        .build();
+    assert_eq!(StructOptional { a: None, b: None, c: Some(3), d: Some(vec![1]) }, built);
+
+    let built = BuilderOptional::new()
+        .with_c(3u8)
+        .no_c()
+        .clear_d()
+        .build();
+    assert_eq!(StructOptional { a: None, b: Some(1), c: None, d: None }, built);
+}
+
+#[test]
+fn required() {
+    assert_eq!(StructRequired { a: None, b: 2 }, BuilderRequired::new().a(None).build());
+    assert_eq!(StructRequired { a: Some(1), b: 3 }, BuilderRequired::new().b(3).a(Some(1)).build());
+
+    let built = BuilderRequiredAll::new().a(None).b(2).build();
+    assert_eq!(StructRequiredAll { a: None, b: Some(2), c: Some(3) }, built);
+}
+
+#[test]
+fn option_paths() {
+    let built = BuilderOptionPaths::new()
+        .c(custom::Option(3))
+        .build();
+    assert_eq!(StructOptionPaths { a: None, b: None::<String>, c: custom::Option(3), d: None, e: None }, built);
+
+    let built = BuilderOptionPaths::new()
+        .a(1)
+        .b("b")
+        .c(custom::Option(3))
+        .d("d")
+        .e_opt(Some(5))
+        .build();
+    assert_eq!(StructOptionPaths { a: Some(1), b: Some("b"), c: custom::Option(3), d: Some("d"), e: Some(5) }, built);
+}
+
+#[test]
+fn validate_field() {
+    let error = BuilderValidated::new().port(80u16).err().unwrap();
+    assert_eq!("port", error.field());
+    assert_eq!(&PortError(80), error.error());
+
+    let builder = BuilderValidated::new().port(8080u16).unwrap();
+    let error = builder.name("").err().unwrap();
+    assert_eq!("invalid value for field `name`: empty name", error.to_string());
+
+    let built = BuilderValidated::new()
+        .port(8080u16).unwrap()
+        .name_opt(None::<String>).unwrap()
+        .build();
+    assert_eq!(StructValidated { port: 8080, name: None, fallback: 80 }, built);
+    assert!(BuilderValidated::new().name_opt(Some(String::new())).is_err());
+
+    let built = BuilderValidated::new()
+        .try_fallback(8081u16).unwrap()
+        .port(8080u16).unwrap()
+        .name("name").unwrap()
+        .build();
+    assert_eq!(StructValidated { port: 8080, name: Some("name".to_owned()), fallback: 8081 }, built);
+    assert_eq!(Err(PortError(1)), BuilderValidated::new().try_fallback(1u16).map_err(|e| e.into_error()).map(|_| ()));
+}
+
+#[test]
+fn generated_error() {
+    let error = build_errors(80).unwrap_err();
+    assert_eq!("invalid value for field `port`: port 80 is reserved", error.to_string());
+    assert!(matches!(error, ErrorsError::Field(_)));
+    assert!(matches!(build_errors(8080), Err(ErrorsError::Validation(PortError(8080)))));
+    assert_eq!(StructErrors { port: 8081 }, build_errors(8081).unwrap());
+}
+
+#[test]
+fn check() {
+    let error = BuilderChecked::new().from(2).to(1).build().unwrap_err();
+    assert_eq!("from is after to", error.to_string());
+    let error = BuilderChecked::new().from(2).to(20).build().unwrap_err();
+    assert_eq!("length 18 is too long", error.to_string());
+    assert_eq!(StructChecked { from: 2, to: 3 }, BuilderChecked::new().from(2).to(3).build().unwrap());
+}
+
+#[test]
+fn traits() {
+    let builder = BuilderTraits::default().a(1);
+    assert_eq!(builder, BuilderTraits::new().a(1));
+    assert_ne!(builder, BuilderTraits::new().a(2));
+    assert_ne!(builder, builder.clone().b(1));
+
+    let mut builders = HashSet::new();
+    builders.insert(builder.clone());
+    builders.insert(builder.clone().b(2));
+    builders.insert(BuilderTraits::new().a(1));
+    assert_eq!(2, builders.len());
+
+    // Derived traits are implemented for the states too.
+    assert!(builder < BuilderTraits::new().a(2));
+    assert_eq!(std::cmp::Ordering::Less, builder.cmp(&builder.clone().b(1)));
+    assert_eq!(StructTraits { a: 1, b: None }, builder.build());
+}
+
+#[test]
+fn debug() {
+    let builder = BuilderDebug::new();
+    assert_eq!("BuilderDebug { user: <unset>, password: <unset>, retries: <default>, token: None, type: None, .. }", format!("{:?}", builder));
+    let builder = builder
+        .user("bob".to_owned())
+        .password(Secret("hunter2"))
+        .key(Secret("key"))
+        .retries(5)
+        .token(Secret("token"))
+        .r#type(1);
+    assert_eq!("BuilderDebug { user: \"bob\", password: <redacted>, retries: 5, token: <redacted>, type: Some(1), .. }", format!("{:?}", builder));
+    let built = builder.build();
+    assert_eq!(("bob", 5, Some(1)), (&built.user[..], built.retries, built.r#type));
+    assert_eq!(("hunter2", "key", Some("token")), (built.password.0, built.key.0, built.token.map(|t| t.0)));
+}
+
+#[test]
+fn to_builder() {
+    let original = BuilderCopied::new()
+        .a("a".to_owned())
+        .b(1)
+        .c(2)
+        .clear_d()
+        .build();
+    let copy = original.to_builder()
+        .a("b".to_owned())
+        .clear_b()
+        .build();
+    // Values of the original are kept even if they differ from the defaults.
+    assert_eq!(StructCopied { a: "b".to_owned(), b: None, c: 2, d: None }, copy);
+    assert_eq!(original, original.clone().into_builder().build());
+
+    let moved = StructMoved(Secret("secret"), 1).into_builder()
+        .field_1(2)
+        .build();
+    assert_eq!(("secret", 2), (moved.0 .0, moved.1));
+}
+
+#[test]
+fn qualified_traits() {
+    // Traits given by their paths have the same generated impls as the ones given by their names.
+    let builder = BuilderQualified::default().password("hunter2".to_owned());
+    assert_eq!("BuilderQualified { user: <unset>, password: <redacted> }", format!("{:?}", builder));
+    assert_eq!(builder, BuilderQualified::new().password("hunter2".to_owned()));
+    let built = builder.clone().user("bob".to_owned()).build();
+    assert_eq!(("bob", "hunter2"), (&built.user[..], &built.password[..]));
+}
+
+#[test]
+fn shadowed_names() {
+    let builder = shadowed::BuilderShadowed::new().b(2);
+    assert_eq!("BuilderShadowed { a: <unset>, b: Some(2) }", format!("{:?}", builder.clone()));
+    assert_eq!(Some(2), builder.a(1).build().b);
 }
@@ -0,0 +1,107 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ConfigBuilder")]
+#[builder_derive(Clone, Debug)]
+pub enum Config {
+    Tcp {
+        host: String,
+        port: u16,
+        #[builder_default = "30"]
+        timeout: u64,
+    },
+    #[builder_prefix = "with_"]
+    Unix {
+        path: String,
+        mode: Option<u32>,
+    },
+    #[builder_names(new = "local", build = "finish")]
+    Local(u32),
+    Stdio,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "EitherBuilder")]
+#[builder_validate(validator = "Either::validate", error = "String")]
+pub enum Either<L: Eq, R> {
+    Left { left: L },
+    Right { right: R },
+}
+
+impl<L: Eq, R> Either<L, R> {
+    fn validate(self) -> Result<Self, String> {
+        Ok(self)
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ExprBuilder")]
+pub enum Expr {
+    Match { value: u32 },
+    Loop { body: u32 },
+    Type(String),
+    Super,
+    HTTPServer(String),
+    IPv4Addr(u32),
+    IOError,
+}
+
+#[test]
+fn variants() {
+    let built = ConfigBuilder::tcp()
+        .port(80)
+        .host("localhost".to_owned())
+        .build();
+    assert_eq!(Config::Tcp { host: "localhost".to_owned(), port: 80, timeout: 30 }, built);
+    let builder = ConfigBuilder::unix()
+        .with_path("/tmp/socket".to_owned());
+    let built = builder.clone()
+        .with_mode(0o600)
+        .build();
+    assert_eq!(Config::Unix { path: "/tmp/socket".to_owned(), mode: Some(0o600) }, built);
+    // Prefix is included in the names of the setters of optional `Option`.
+    let built = builder.clone()
+        .with_mode_opt(Some(0o600))
+        .clear_with_mode()
+        .build();
+    assert_eq!(Config::Unix { path: "/tmp/socket".to_owned(), mode: None }, built);
+    let built = builder.build();
+    assert_eq!(Config::Unix { path: "/tmp/socket".to_owned(), mode: None }, built);
+    let built = ConfigBuilder::local()
+        .field_0(7)
+        .finish();
+    assert_eq!(Config::Local(7), built);
+    let built = ConfigBuilder::stdio().build();
+    assert_eq!(Config::Stdio, built);
+}
+
+#[test]
+fn generic_variants() {
+    let built = EitherBuilder::left()
+        .left(1)
+        .build();
+    assert_eq!(Ok(Either::<_, ()>::Left { left: 1 }), built);
+    let built = EitherBuilder::right()
+        .right("right")
+        .build();
+    assert_eq!(Ok(Either::<(), _>::Right { right: "right" }), built);
+}
+
+#[test]
+fn keyword_variants() {
+    // Constructors whose names would be keywords are raw identifiers.
+    assert_eq!(Expr::Match { value: 1 }, ExprBuilder::r#match().value(1).build());
+    assert_eq!(Expr::Loop { body: 2 }, ExprBuilder::r#loop().body(2).build());
+    assert_eq!(Expr::Type("u32".to_owned()), ExprBuilder::r#type().field_0("u32".to_owned()).build());
+    // Keywords that can't be raw identifiers get trailing underscore.
+    assert_eq!(Expr::Super, ExprBuilder::super_().build());
+}
+
+#[test]
+fn acronym_variants() {
+    // Runs of capitals are kept as one word.
+    assert_eq!(Expr::HTTPServer("a".to_owned()), ExprBuilder::http_server().field_0("a".to_owned()).build());
+    assert_eq!(Expr::IPv4Addr(1), ExprBuilder::ipv4_addr().field_0(1).build());
+    assert_eq!(Expr::IOError, ExprBuilder::io_error().build());
+}
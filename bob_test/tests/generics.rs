@@ -0,0 +1,137 @@
+#[macro_use]
+extern crate bob;
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+#[derive(Builder)]
+#[builder_names(builder = "CollectBuilder")]
+#[builder_derive(Debug)]
+pub struct Collect<I> where I: Iterator, I::Item: Debug {
+    iter: I,
+    first: Option<I::Item>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BufferBuilder")]
+#[builder_derive(Clone, Debug)]
+pub struct Buffer<const N: usize, T: Copy + Default = u8> {
+    data: [T; N],
+    #[builder_default = "N"]
+    len: usize,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "WrapperBuilder")]
+pub struct Wrapper<T = String>(T, #[builder_default] Option<T>);
+
+#[derive(Builder)]
+#[builder_names(builder = "PairBuilder")]
+#[builder_derive(Clone)]
+pub struct Pair<A, B> where A: PartialEq<B> {
+    left: A,
+    right: B,
+}
+
+impl<A: PartialEq<B>, B> Pair<A, B> {
+    fn equal(&self) -> bool {
+        self.left == self.right
+    }
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "MapperBuilder")]
+pub struct Mapper<F> where F: for<'a> Fn(&'a str) -> &'a str {
+    map: F,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ShapeBuilder")]
+pub enum Shape<const D: usize, T> where T: Copy {
+    Point { at: [T; D] },
+    Line { from: [T; D], to: [T; D] },
+}
+
+/// Type that doesn't implement any traits.
+pub struct Opaque;
+
+#[derive(Builder)]
+#[builder_names(builder = "SharedBuilder")]
+#[builder_derive(Clone, Debug, PartialEq)]
+pub struct Shared<T, U> {
+    value: Rc<T>,
+    #[builder_default]
+    count: u32,
+    _marker: Option<PhantomData<U>>,
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "BoundedBuilder")]
+#[builder_derive(Clone(bound = "T: Copy"), Debug(bound = "T: Debug + Copy"))]
+pub struct Bounded<T> {
+    value: T,
+}
+
+#[test]
+fn where_clauses() {
+    let built = CollectBuilder::new()
+        .iter(vec![1, 2, 3].into_iter())
+        .first(1)
+        .build();
+    assert_eq!(Some(1), built.first);
+    assert_eq!(vec![1, 2, 3], built.iter.collect::<Vec<_>>());
+
+    let builder = PairBuilder::new().left("left".to_owned());
+    assert!(builder.clone().right("left").build().equal());
+    assert!(!builder.right("right").build().equal());
+
+    let built = MapperBuilder::new()
+        .map(|s: &str| s.trim())
+        .build();
+    assert_eq!("trimmed", (built.map)(" trimmed "));
+}
+
+#[test]
+fn const_generics() {
+    let builder = BufferBuilder::new().data([1u8, 2, 3]);
+    let built = builder.clone().build();
+    assert_eq!(Buffer { data: [1, 2, 3], len: 3 }, built);
+    assert_eq!(1, builder.len(1).build().len);
+
+    assert_eq!(Shape::Point { at: [1, 2] }, ShapeBuilder::point().at([1, 2]).build());
+    assert_eq!(Shape::Line { from: [0.0; 3], to: [1.0; 3] }, ShapeBuilder::line().to([1.0; 3]).from([0.0; 3]).build());
+}
+
+#[test]
+fn defaulted_params() {
+    let built: Wrapper = WrapperBuilder::new()
+        .field_0("value".to_owned())
+        .build();
+    assert_eq!(Wrapper("value".to_owned(), None), built);
+    let built: Wrapper<i32> = WrapperBuilder::new()
+        .field_1(2)
+        .field_0(1)
+        .build();
+    assert_eq!(Wrapper(1, Some(2)), built);
+}
+
+#[test]
+fn bounds() {
+    // Only the types of the fields are required to implement the traits, not the parameters.
+    let builder = SharedBuilder::new().value(Rc::new(Opaque));
+    let cloned = builder.clone().count(1);
+    let (built, cloned): (Shared<Opaque, Opaque>, Shared<Opaque, Opaque>) = (builder.build(), cloned.build());
+    assert!(Rc::ptr_eq(&built.value, &cloned.value));
+    assert_eq!((0, 1), (built.count, cloned.count));
+
+    let builder = SharedBuilder::new().value(Rc::new(1)).count(2);
+    assert_eq!(builder.clone(), builder);
+    let _: Shared<u32, Opaque> = builder.clone().build();
+    assert_eq!("SharedBuilder { value: 1, count: 2, _marker: None }", format!("{:?}", builder));
+    assert_ne!(builder.clone().count(3), builder);
+
+    let builder = BoundedBuilder::new().value(1);
+    assert_eq!(1, builder.clone().build().value);
+    assert!(format!("{:?}", builder).starts_with("BoundedBuilder"));
+}
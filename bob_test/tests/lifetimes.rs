@@ -0,0 +1,91 @@
+#[macro_use]
+extern crate bob;
+
+use std::marker::PhantomData;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "RequestBuilder")]
+#[builder_derive(Clone, Debug)]
+pub struct Request<'a> {
+    path: &'a str,
+    query: Option<&'a str>,
+    #[builder_default]
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+#[derive(Builder, Debug)]
+#[builder_names(builder = "NestedBuilder")]
+#[builder_derive(Clone, Debug)]
+pub struct Nested<'a, 'b: 'a, T: 'b + ?Sized> where T: PartialEq {
+    outer: &'a Request<'b>,
+    inner: &'b T,
+    label: Option<&'a str>,
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "PhantomBuilder")]
+pub struct Phantom<'a, T> {
+    // Lifetime and type parameter are only used through phantom data.
+    _marker: Option<PhantomData<&'a T>>,
+    value: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "SourceBuilder")]
+pub enum Source<'a> {
+    Borrowed { text: &'a str },
+    Owned(String),
+    Empty,
+}
+
+fn request<'a>(path: &'a str, query: &'a str) -> Request<'a> {
+    RequestBuilder::new()
+        .path(path)
+        .query(query)
+        .build()
+}
+
+#[test]
+fn borrowed() {
+    let path = "/index.html".to_owned();
+    let query = "page=1".to_owned();
+    let built = request(&path, &query);
+    assert_eq!("/index.html", built.path);
+    assert_eq!(Some("page=1"), built.query);
+    assert!(built.headers.is_empty());
+
+    let builder = RequestBuilder::new().path(&path);
+    let built = builder.clone()
+        .headers(vec![("Accept", "text/html")])
+        .build();
+    assert_eq!(vec![("Accept", "text/html")], built.headers);
+    assert_eq!(None, builder.build().query);
+}
+
+#[test]
+fn bounded() {
+    let path = "/".to_owned();
+    let outer = request(&path, "");
+    let inner = "inner".to_owned();
+    let built = NestedBuilder::new()
+        .inner(&inner[..])
+        .outer(&outer)
+        .label("label")
+        .build();
+    assert_eq!("inner", built.inner);
+    assert_eq!("/", built.outer.path);
+    assert_eq!(Some("label"), built.label);
+
+    let built: Phantom<'static, String> = PhantomBuilder::new()
+        .value(1)
+        .build();
+    assert_eq!(1, built.value);
+}
+
+#[test]
+fn variants() {
+    let text = "text".to_owned();
+    assert_eq!(Source::Borrowed { text: "text" }, SourceBuilder::borrowed().text(&text).build());
+    assert_eq!(Source::Owned("text".to_owned()), SourceBuilder::owned().field_0(text.clone()).build());
+    assert_eq!(Source::Empty, SourceBuilder::empty().build());
+}
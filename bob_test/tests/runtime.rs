@@ -0,0 +1,294 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ServerBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Clone, Debug)]
+pub struct Server {
+    #[builder_validate_field = "validate_host"]
+    host: String,
+    port: u16,
+    name: Option<String>,
+    #[builder_default = "30"]
+    timeout: u64,
+    #[builder_each = "alias"]
+    aliases: Vec<String>,
+}
+
+fn validate_host(host: String) -> Result<String, &'static str> {
+    if host.contains(' ') {
+        Err("host contains space")
+    } else {
+        Ok(host)
+    }
+}
+
+/// Builders in runtime mode can be stored without naming the states of their fields.
+pub struct Config {
+    server: ServerBuilder,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "PortBuilder")]
+#[builder_mode = "runtime"]
+#[builder_validate(validator = "Port::validate", error = "PortError")]
+pub struct Port<T> {
+    number: u16,
+    protocol: T,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PortError {
+    Missing(Vec<&'static str>),
+    Reserved,
+}
+
+impl From<PortBuilderMissingFields> for PortError {
+    fn from(e: PortBuilderMissingFields) -> PortError {
+        PortError::Missing(e.fields().to_vec())
+    }
+}
+
+impl<T> Port<T> {
+    fn validate(self) -> Result<Self, PortError> {
+        if self.number < 1024 {
+            Err(PortError::Reserved)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder_names(builder = "OwnedBuilder")]
+#[builder_mode = "runtime"]
+#[builder_setter_style = "owned"]
+#[builder_to_builder]
+pub struct Owned {
+    a: u32,
+    b: Option<u32>,
+}
+
+/// Type that doesn't implement Clone.
+#[derive(Debug, PartialEq)]
+pub struct NoClone(u32);
+
+/// Builder is generated even if fields don't implement Clone, and it can be built by consuming it.
+#[derive(Builder)]
+#[builder_names(builder = "CallbackBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Debug)]
+pub struct Callback {
+    #[builder_debug(skip)]
+    callback: Box<dyn Fn() -> u32>,
+    value: NoClone,
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "MutRefBuilder")]
+#[builder_setter_style = "mut_ref"]
+#[builder_derive(Debug)]
+pub struct MutRef {
+    value: NoClone,
+    extra: Option<NoClone>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ParentBuilder")]
+#[builder_mode = "runtime"]
+pub struct Parent {
+    #[builder_required]
+    id: Option<u32>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "AccountBuilder")]
+#[builder_mode = "runtime"]
+#[builder_error]
+#[builder_validate(check = "Account::check_balance", validator = "Account::validate", check = "Account::check_host", error = "String")]
+pub struct Account {
+    #[builder_validate_field = "validate_host"]
+    host: String,
+    balance: i64,
+}
+
+impl Account {
+    fn validate(mut self) -> Result<Self, String> {
+        if self.balance < 0 {
+            Err("negative balance".to_owned())
+        } else {
+            self.host = self.host.to_lowercase();
+            Ok(self)
+        }
+    }
+
+    fn check_balance(&self) -> Result<(), &'static str> {
+        if self.balance > 1000 {
+            Err("too large balance")
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_host(&self) -> Result<(), String> {
+        if self.host == "localhost" && self.balance > 100 {
+            Err(format!("too large balance for {}", self.host))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn open(host: Option<&str>, balance: i64) -> Result<Account, AccountBuilderError> {
+    let mut builder = AccountBuilder::new();
+    if let Some(host) = host {
+        builder.host(host.to_owned())?;
+    }
+    builder.balance(balance);
+    builder.build()
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "AddressBuilder")]
+#[builder_mode = "runtime"]
+pub enum Address {
+    Tcp { host: String, port: u16 },
+    Unix(String),
+}
+
+#[test]
+fn missing() {
+    let mut builder = ServerBuilder::new();
+    let error = builder.build().unwrap_err();
+    assert_eq!(&["host", "port"], error.fields());
+    assert_eq!("required fields aren't set: host, port", error.to_string());
+    builder.port(80);
+    assert_eq!(&["host"], builder.build().unwrap_err().fields());
+    // Invalid value isn't stored.
+    assert_eq!("host", builder.host("local host".to_owned()).unwrap_err().field());
+    assert_eq!(&["host"], builder.build().unwrap_err().fields());
+}
+
+#[test]
+fn build() {
+    let mut config = Config { server: ServerBuilder::new() };
+    config.server
+        .host("localhost".to_owned()).unwrap()
+        .port(8080);
+    for alias in ["a", "b"].iter() {
+        config.server.alias(alias.to_string());
+    }
+    let built = config.server.build().unwrap();
+    assert_eq!(Server {
+        host: "localhost".to_owned(),
+        port: 8080,
+        name: None,
+        timeout: 30,
+        aliases: vec!["a".to_owned(), "b".to_owned()],
+    }, built);
+
+    // Builder can be reused after building.
+    let mut builder = config.server.clone();
+    if built.port == 8080 {
+        builder.name("server".to_owned()).timeout(1);
+    }
+    let built = builder.build().unwrap();
+    assert_eq!(Some("server".to_owned()), built.name);
+    assert_eq!(1, built.timeout);
+    assert_eq!(None, config.server.build().unwrap().name);
+
+    config.server.name_opt(Some("server".to_owned()));
+    assert_eq!(Some("server".to_owned()), config.server.build().unwrap().name);
+    config.server.clear_name();
+    assert_eq!(None, config.server.build().unwrap().name);
+}
+
+#[test]
+fn validate() {
+    let mut builder = PortBuilder::new();
+    assert_eq!(Err(PortError::Missing(vec!["number", "protocol"])), builder.build());
+    builder.number(80).protocol("tcp");
+    assert_eq!(Err(PortError::Reserved), builder.build());
+    builder.number(8080);
+    assert_eq!(Ok(Port { number: 8080, protocol: "tcp" }), builder.build());
+}
+
+#[test]
+fn variants() {
+    let mut builder = AddressBuilder::tcp();
+    builder.host("localhost".to_owned());
+    assert_eq!(&["port"], builder.build().unwrap_err().fields());
+    builder.port(80);
+    assert_eq!(Ok(Address::Tcp { host: "localhost".to_owned(), port: 80 }), builder.build());
+    assert_eq!(&["field_0"], AddressBuilder::unix().build().unwrap_err().fields());
+}
+
+#[test]
+fn required() {
+    let mut builder = ParentBuilder::new();
+    assert_eq!(&["id"], builder.build().unwrap_err().fields());
+    builder.id(None);
+    assert_eq!(Ok(Parent { id: None }), builder.build());
+}
+
+#[test]
+fn owned() {
+    let builder = OwnedBuilder::new().b(2);
+    assert_eq!(&["a"], builder.build().unwrap_err().fields());
+    assert_eq!(Ok(Owned { a: 1, b: Some(2) }), builder.a(1).build());
+}
+
+#[test]
+fn to_builder() {
+    let built = Owned { a: 1, b: Some(2) };
+    assert_eq!(Ok(Owned { a: 3, b: Some(2) }), built.to_builder().a(3).build());
+    assert_eq!(Ok(Owned { a: 1, b: None }), built.into_builder().clear_b().build());
+}
+
+#[test]
+fn generated_error() {
+    match open(Some("local host"), 1) {
+        Err(AccountBuilderError::Field(error)) => {
+            assert_eq!("host", error.field());
+            assert_eq!("invalid value for field `host`: host contains space", error.to_string());
+        },
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match open(None, 1) {
+        Err(AccountBuilderError::MissingFields(error)) => assert_eq!(&["host"], error.fields()),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    // Errors of the builder are given as sources, but the error of the validator isn't.
+    let source = |error: AccountBuilderError| std::error::Error::source(&error).map(|e| e.to_string());
+    assert_eq!(Some("invalid value for field `host`: host contains space".to_owned()), source(open(Some("local host"), 1).unwrap_err()));
+    assert_eq!(Some("required fields aren't set: host".to_owned()), source(open(None, 1).unwrap_err()));
+    assert_eq!(None, source(open(Some("localhost"), -1).unwrap_err()));
+    let error = open(Some("localhost"), -1).unwrap_err();
+    assert_eq!("negative balance", error.to_string());
+    // Checks and the validator are run in the order they are given.
+    assert_eq!("too large balance", open(Some("localhost"), 1001).unwrap_err().to_string());
+    assert_eq!("too large balance for localhost", open(Some("localhost"), 101).unwrap_err().to_string());
+    // Check given after the validator sees the value returned by it.
+    assert_eq!("too large balance for localhost", open(Some("LocalHost"), 101).unwrap_err().to_string());
+    assert_eq!(Account { host: "example.com".to_owned(), balance: 101 }, open(Some("example.com"), 101).unwrap());
+    assert_eq!(Account { host: "localhost".to_owned(), balance: 1 }, open(Some("localhost"), 1).unwrap());
+}
+
+#[test]
+fn not_clone() {
+    let mut builder = CallbackBuilder::new();
+    builder.callback(Box::new(|| 1)).value(NoClone(2));
+    assert_eq!("CallbackBuilder { value: NoClone(2), .. }", format!("{:?}", builder));
+    let built = builder.into_build().unwrap();
+    assert_eq!(1, (built.callback)());
+    assert_eq!(NoClone(2), built.value);
+    let error = CallbackBuilder::new().into_build().err().unwrap();
+    assert_eq!(&["callback", "value"], error.fields());
+
+    let mut builder = MutRefBuilder::new().value(NoClone(3));
+    builder.extra(NoClone(4));
+    assert_eq!("MutRefBuilder { value: NoClone(3), extra: Some(NoClone(4)) }", format!("{:?}", builder));
+    let built = builder.into_build();
+    assert_eq!((NoClone(3), Some(NoClone(4))), (built.value, built.extra));
+}
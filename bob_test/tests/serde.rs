@@ -0,0 +1,139 @@
+#[macro_use]
+extern crate bob;
+
+use serde_json::json;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "ConfigBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    #[builder_validate_field = "validate_host"]
+    host: String,
+    port: u16,
+    name: Option<String>,
+    #[builder_default = "30"]
+    timeout: u64,
+    #[builder_default = "Some(\"admin\".to_owned())"]
+    user: Option<String>,
+    #[builder_each = "alias"]
+    aliases: Vec<String>,
+}
+
+fn validate_host(host: String) -> Result<String, &'static str> {
+    if host.contains(' ') {
+        Err("host contains space")
+    } else {
+        Ok(host)
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "LimitsBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Serialize, Deserialize)]
+pub struct Limits<T> {
+    min: T,
+    max: T,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "QualifiedBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(serde::Serialize, serde::Deserialize)]
+pub struct Qualified {
+    value: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "BorrowedBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Deserialize)]
+pub struct Borrowed<'a, 'b> {
+    name: &'a str,
+    tags: Vec<&'b str>,
+}
+
+/// Lifetime of the built item can have the same name as the lifetime of the deserialized data.
+#[derive(Builder, Debug, PartialEq)]
+#[builder_names(builder = "NamedBuilder")]
+#[builder_mode = "runtime"]
+#[builder_derive(Deserialize)]
+pub struct Named<'de> {
+    name: &'de str,
+}
+
+#[test]
+fn deserialize() {
+    let mut builder: ConfigBuilder = serde_json::from_value(json!({
+        "host": "localhost",
+        "timeout": 10,
+        "aliases": ["a"],
+    })).unwrap();
+    assert_eq!(&["port"], builder.build().unwrap_err().fields());
+    builder.port(8080).alias("b".to_owned());
+    assert_eq!(Config {
+        host: "localhost".to_owned(),
+        port: 8080,
+        name: None,
+        timeout: 10,
+        user: Some("admin".to_owned()),
+        aliases: vec!["a".to_owned(), "b".to_owned()],
+    }, builder.build().unwrap());
+
+    let limits: LimitsBuilder<i32> = serde_json::from_str(r#"{"min": 1, "max": 2}"#).unwrap();
+    assert_eq!(Limits { min: 1, max: 2 }, limits.build().unwrap());
+}
+
+#[test]
+fn deserialize_errors() {
+    let error = serde_json::from_value::<ConfigBuilder>(json!({ "hots": "localhost" })).unwrap_err();
+    assert!(error.to_string().starts_with("unknown field `hots`, expected one of `host`, `port`"), "{}", error);
+    let error = serde_json::from_str::<ConfigBuilder>(r#"{"port": 1, "port": 2}"#).unwrap_err();
+    assert!(error.to_string().starts_with("duplicate field `port`"), "{}", error);
+    // Optional `Option` that is `None` is still seen.
+    let error = serde_json::from_str::<ConfigBuilder>(r#"{"name": null, "name": "second"}"#).unwrap_err();
+    assert!(error.to_string().starts_with("duplicate field `name`"), "{}", error);
+    // Values are validated the same way as in setters.
+    let error = serde_json::from_value::<ConfigBuilder>(json!({ "host": "local host" })).unwrap_err();
+    assert_eq!("invalid value for field `host`: host contains space", error.to_string());
+    assert!(serde_json::from_value::<ConfigBuilder>(json!([1, 2])).is_err());
+}
+
+#[test]
+fn serialize() {
+    let mut builder = ConfigBuilder::new();
+    assert_eq!(json!({}), serde_json::to_value(&builder).unwrap());
+    builder.port(80).name("server".to_owned()).clear_user();
+    // Default values aren't serialized, but explicitly cleared `Option` is.
+    let value = serde_json::to_value(&builder).unwrap();
+    assert_eq!(json!({ "port": 80, "name": "server", "user": null }), value);
+    let deserialized: ConfigBuilder = serde_json::from_value(value).unwrap();
+    assert_eq!(builder, deserialized);
+    assert_eq!(None, deserialized.build().unwrap_err().fields().iter().find(|&&f| f == "port"));
+
+    let mut limits = LimitsBuilder::new();
+    limits.max(1.5);
+    assert_eq!(r#"{"max":1.5}"#, serde_json::to_string(&limits).unwrap());
+}
+
+#[test]
+fn qualified_traits() {
+    let builder: QualifiedBuilder = serde_json::from_str(r#"{"value": 1}"#).unwrap();
+    assert_eq!(r#"{"value":1}"#, serde_json::to_string(&builder).unwrap());
+    assert_eq!(Ok(Qualified { value: 1 }), builder.build());
+}
+
+#[test]
+fn borrowed() {
+    let input = r#"{"name": "bob", "tags": ["a", "b"]}"#.to_owned();
+    let builder: BorrowedBuilder = serde_json::from_str(&input).unwrap();
+    assert_eq!(Ok(Borrowed { name: "bob", tags: vec!["a", "b"] }), builder.build());
+}
+
+#[test]
+fn lifetime_named_de() {
+    let input = r#"{"name": "bob"}"#.to_owned();
+    let builder: NamedBuilder = serde_json::from_str(&input).unwrap();
+    assert_eq!(Ok(Named { name: "bob" }), builder.build());
+}
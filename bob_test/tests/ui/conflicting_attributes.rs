@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder)]
+struct MyStruct {
+    #[builder_required]
+    #[builder_optional]
+    a: Option<i32>,
+    #[builder_required]
+    #[builder_default]
+    b: i32,
+    #[builder_required]
+    #[builder_each = "item"]
+    c: Vec<i32>,
+    #[builder_optional]
+    d: i32,
+    #[builder_option_names(opt = "maybe_e")]
+    e: i32,
+    #[builder_each = "item"]
+    #[builder_validate_field = "validate"]
+    f: Vec<i32>,
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "TypestateBuilder")]
+#[builder_derive(Serialize, Deserialize)]
+struct Typestate {
+    a: i32,
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "MyEnumBuilder")]
+#[builder_to_builder]
+enum MyEnum {
+    A { a: i32 },
+}
+
+#[derive(Builder)]
+#[builder_names(builder = "InfallibleBuilder")]
+#[builder_error]
+struct Infallible {
+    a: i32,
+}
+
+fn main() {}
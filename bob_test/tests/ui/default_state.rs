@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder)]
+#[builder_derive(std::default::Default)]
+struct MyStruct {
+    a: i32,
+}
+
+fn main() {
+    // Only builder that doesn't have any fields set can be created with `Default`.
+    let builder: Builder<_builder::I> = Default::default();
+    let _ = builder.build();
+}
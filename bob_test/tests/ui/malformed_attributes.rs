@@ -0,0 +1,28 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder)]
+#[builder_names(builder = "MyBuilder", biuld = "finish", new = 1)]
+#[builder_validate(validator = "not a path")]
+#[builder_prefix = "set_"]
+#[builder_prefix = "with_"]
+#[builder_default]
+#[builder_option_aliases(Maybe = "Option")]
+#[builder_derive(PartialEq, PartialOrd(bound = ""), Default(bound = "not a bound"))]
+struct MyStruct {
+    #[builder_default = "1 +"]
+    a: i32,
+    #[builder_name = "not an identifier"]
+    #[builder_docs(builder = "Not for fields.")]
+    b: i32,
+    #[builder_into(all)]
+    #[builder_debug(skip, redact = "yes")]
+    c: i32,
+    #[builder_debug(skip, redact)]
+    d: i32,
+}
+
+fn main() {
+    // Builder is still generated so that using it doesn't cause more errors.
+    let _ = MyBuilder::new().set_b(1);
+}
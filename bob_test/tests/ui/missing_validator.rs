@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder)]
+#[builder_validate(error = "String")]
+struct MyStruct {
+    a: i32,
+}
+
+fn main() {
+    let _ = Builder::new().a(1).build();
+}
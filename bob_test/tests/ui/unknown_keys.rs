@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate bob;
+
+#[derive(Builder)]
+#[builder_names(biuld = "finish", constructor = "create")]
+#[builder_validate(validator = "validate", eror = "String")]
+#[builder_docs(buidler = "Builder.", setter = "Not for structs.")]
+#[builder_derive(Clnoe, Debug, PartialEq)]
+#[builder_mode = "runtim"]
+#[builder_setter_style = "ref"]
+struct MyStruct {
+    #[builder_docs(seter = "Setter.")]
+    #[builder_debug(redcat)]
+    a: i32,
+}
+
+fn validate(s: MyStruct) -> MyStruct {
+    s
+}
+
+fn main() {
+    let _ = Builder::new().a(1).build();
+}